@@ -0,0 +1,255 @@
+use fifteen_puzzle::SolveResult;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Result of solving a single puzzle with a single strategy.
+pub struct BatchRecord {
+    /// Name of the puzzle file, without the extension.
+    pub puzzle: String,
+    pub strategy: String,
+    /// Order or metric of the strategy.
+    pub parameter: String,
+    pub result: SolveResult,
+}
+
+/// Returns puzzle files matching the pattern, sorted by name.
+/// Pattern is either a directory, in which case all of its files are returned,
+/// or a path whose file name can contain `*` (any characters) and `?` (a single character).
+pub fn find_puzzles(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+
+    let (directory, file_pattern) = if path.is_dir() {
+        (path, "*")
+    } else {
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let file_pattern = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        (directory, file_pattern)
+    };
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.file_type()?.is_file() && matches(file_pattern, &name.to_string_lossy()) {
+            files.push(entry.path());
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Returns whether the name matches the pattern with `*` and `?` wildcards.
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // matched[j] tells whether the pattern read so far matches first j characters of the name.
+    let mut matched = vec![false; name.len() + 1];
+    matched[0] = true;
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matched[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matched[j - 1],
+                _ => j > 0 && matched[j - 1] && name[j - 1] == p,
+            };
+        }
+        matched = next;
+    }
+    matched[name.len()]
+}
+
+/// Runs every job on a pool of threads, and returns the results in the order of the jobs,
+/// no matter which thread ran them or when they finished.
+pub fn run_parallel<T, R, F>(jobs: &[T], threads: usize, run: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    // Each thread takes the next job nobody took yet, until there are none left.
+    let next_job = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, jobs.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            break;
+                        };
+                        results.push((index, run(job)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Writes the records as JSON if the file name ends with `.json`, and as CSV otherwise.
+pub fn write_report(path: &str, records: &[BatchRecord]) -> io::Result<()> {
+    let contents = if path.ends_with(".json") {
+        to_json(records)
+    } else {
+        to_csv(records)
+    };
+    fs::write(path, contents)
+}
+
+/// Returns the length of the solution, or -1 if there is none.
+fn solution_length(result: &SolveResult) -> i64 {
+    result.path.as_ref().map_or(-1, |path| path.len() as i64)
+}
+
+/// Returns time spent in milliseconds.
+fn time_ms(result: &SolveResult) -> f64 {
+    result.time_spent as f64 * 10.0_f64.powi(-6)
+}
+
+fn to_csv(records: &[BatchRecord]) -> String {
+    let mut csv = String::from(
+        "puzzle,strategy,parameter,solution_length,visited_states,processed_states,max_depth,time_ms,outcome\n",
+    );
+    for record in records {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{:.3},{}\n",
+            csv_field(&record.puzzle),
+            csv_field(&record.strategy),
+            csv_field(&record.parameter),
+            solution_length(&record.result),
+            record.result.visited_states,
+            record.result.processed_states,
+            record.result.max_depth,
+            time_ms(&record.result),
+            record.result.outcome
+        ));
+    }
+    csv
+}
+
+/// Quotes a CSV field if it contains characters which would break the row.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_json(records: &[BatchRecord]) -> String {
+    let rows: Vec<String> = records
+        .iter()
+        .map(|record| {
+            format!(
+                "  {{\"puzzle\": {}, \"strategy\": {}, \"parameter\": {}, \"solution_length\": {}, \
+                 \"visited_states\": {}, \"processed_states\": {}, \"max_depth\": {}, \"time_ms\": {:.3}, \"outcome\": {}}}",
+                json_string(&record.puzzle),
+                json_string(&record.strategy),
+                json_string(&record.parameter),
+                solution_length(&record.result),
+                record.result.visited_states,
+                record.result.processed_states,
+                record.result.max_depth,
+                time_ms(&record.result),
+                json_string(&record.result.outcome.to_string())
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            character if character.is_control() => {
+                json.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn parallel_results_keep_the_order_of_the_jobs() {
+        // Early jobs take the longest, so they finish after the ones started later.
+        let jobs: Vec<u64> = (0..24).collect();
+        let results = run_parallel(&jobs, 4, |&job| {
+            thread::sleep(Duration::from_millis((24 - job) % 7 * 3));
+            (job * job, thread::current().id())
+        });
+
+        let squares: Vec<u64> = results.iter().map(|(square, _)| *square).collect();
+        assert_eq!(
+            squares,
+            jobs.iter().map(|job| job * job).collect::<Vec<_>>()
+        );
+        // The jobs really were spread over more than one thread.
+        assert!(results.iter().any(|(_, id)| *id != results[0].1));
+
+        assert!(run_parallel(&[] as &[u64], 4, |&job| job).is_empty());
+        assert_eq!(run_parallel(&jobs, 1, |&job| job), jobs);
+    }
+
+    #[test]
+    fn wildcards_match_like_shell_globs() {
+        assert!(matches("4x4_10_00001.txt", "4x4_10_00001.txt"));
+        assert!(!matches("4x4_10_00001.txt", "4x4_10_00002.txt"));
+        assert!(!matches("4x4", "4x4_10"));
+
+        assert!(matches("*", ""));
+        assert!(matches("*.txt", "4x4_10_00001.txt"));
+        assert!(matches("4x4_*_*.txt", "4x4_10_00001.txt"));
+        assert!(matches("*10*", "4x4_10_00001.txt"));
+        assert!(!matches("*.txt", "4x4_10_00001.csv"));
+        assert!(!matches("3x3_*", "4x4_10_00001.txt"));
+
+        assert!(matches("4x4_1?_*", "4x4_10_00001.txt"));
+        assert!(matches("???", "abc"));
+        assert!(!matches("???", "ab"));
+        assert!(!matches("?", ""));
+        assert!(matches("?*?", "ab"));
+    }
+
+    #[test]
+    fn report_fields_are_escaped() {
+        assert_eq!(csv_field("4x4_10_00001"), "4x4_10_00001");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+
+        assert_eq!(json_string("a,b"), "\"a,b\"");
+        assert_eq!(json_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(json_string("C:\\puzzles"), "\"C:\\\\puzzles\"");
+        assert_eq!(json_string("two\nlines"), "\"two\\u000alines\"");
+    }
+}
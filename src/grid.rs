@@ -0,0 +1,75 @@
+/// Value of a single cell. Boards can have at most `Tile::MAX + 1` cells.
+pub type Tile = u16;
+
+/// Most cells a board can have to be packed into a single integer.
+const PACKED_CELLS: usize = 16;
+/// Bits used by a single cell of a packed board.
+const PACKED_BITS: usize = 4;
+const PACKED_MASK: u64 = 0b1111;
+
+/// Values of the cells of a board in row-major order, 0 being the empty cell.
+/// Every visited state keeps one of these, so small boards are stored as an integer
+/// instead of a heap allocation, which also makes them much cheaper to clone, compare and hash.
+/// Cells only take as many bytes as the largest value on the board needs.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Grid {
+    /// Boards of up to 16 cells, 4 bits per cell, with the first cell in the lowest bits.
+    Packed(u64),
+    /// Boards of up to 256 cells, a byte per cell.
+    Bytes(Vec<u8>),
+    /// Larger boards, two bytes per cell.
+    Words(Vec<u16>),
+}
+
+impl Grid {
+    /// Returns grid of the given cells, which have to be numbered from 0 to `cells.len() - 1`.
+    pub fn new(cells: Vec<Tile>) -> Grid {
+        if cells.len() <= PACKED_CELLS {
+            let mut bits = 0;
+            for (i, value) in cells.iter().enumerate() {
+                bits |= (*value as u64) << (i * PACKED_BITS);
+            }
+            Grid::Packed(bits)
+        } else if cells.len() <= u8::MAX as usize + 1 {
+            Grid::Bytes(cells.into_iter().map(|value| value as u8).collect())
+        } else {
+            Grid::Words(cells)
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Tile {
+        match self {
+            Grid::Packed(bits) => ((bits >> (i * PACKED_BITS)) & PACKED_MASK) as Tile,
+            Grid::Bytes(cells) => cells[i] as Tile,
+            Grid::Words(cells) => cells[i],
+        }
+    }
+
+    /// Returns the number of bytes the cells take outside of the grid itself.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Grid::Packed(_) => 0,
+            Grid::Bytes(cells) => cells.capacity(),
+            Grid::Words(cells) => cells.capacity() * 2,
+        }
+    }
+
+    /// Moves the tile from the `from` cell to the `to` cell, which has to be empty.
+    pub fn slide(&mut self, from: usize, to: usize) {
+        match self {
+            Grid::Packed(bits) => {
+                let value = (*bits >> (from * PACKED_BITS)) & PACKED_MASK;
+                *bits &= !(PACKED_MASK << (from * PACKED_BITS));
+                *bits |= value << (to * PACKED_BITS);
+            }
+            Grid::Bytes(cells) => {
+                cells[to] = cells[from];
+                cells[from] = 0;
+            }
+            Grid::Words(cells) => {
+                cells[to] = cells[from];
+                cells[from] = 0;
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::grid::Tile;
+use crate::pdb::PatternDatabase;
+use crate::puzzle::{Direction, Puzzle};
+use std::fmt::Debug;
+
+/// Estimate of the number of moves needed to solve a board, which guides informed strategies.
+pub trait Heuristic: Debug + Send + Sync {
+    /// Returns the estimate for the board.
+    fn estimate(&self, puzzle: &Puzzle) -> u32;
+
+    /// Returns the estimate for the board right after the empty cell moved in the given direction,
+    /// given the estimate from before the move. Heuristics which know how a single move changes
+    /// the estimate can override it to avoid looking at the whole board, by default the board is estimated again.
+    fn update(&self, puzzle: &Puzzle, _direction: Direction, _previous: u32) -> u32 {
+        self.estimate(puzzle)
+    }
+
+    /// Whether the estimate never exceeds the real number of moves.
+    /// A* and IDA* only find the shortest solutions with admissible heuristics.
+    fn is_admissible(&self) -> bool;
+
+    /// Returns whether the heuristic can estimate boards of the given dimensions.
+    fn supports(&self, _width: usize, _height: usize) -> bool {
+        true
+    }
+
+    /// Returns the pattern database the heuristic looks up, if it's one,
+    /// so that callers can tell which board it was built for.
+    fn pattern_database(&self) -> Option<&PatternDatabase> {
+        None
+    }
+}
+
+/// Number of tiles which aren't in their goal cells.
+#[derive(Debug, Clone, Copy)]
+pub struct Hamming;
+
+/// Sum of the distances of tiles from their goal cells, counting only vertical and horizontal steps.
+#[derive(Debug, Clone, Copy)]
+pub struct Manhattan;
+
+/// Manhattan distance plus two moves for every tile which has to step out of its goal row (or column)
+/// to let the other tiles in it pass.
+#[derive(Debug, Clone, Copy)]
+pub struct LinearConflict;
+
+impl Heuristic for Hamming {
+    fn estimate(&self, puzzle: &Puzzle) -> u32 {
+        puzzle.hamming_metric()
+    }
+
+    /// Only the tile which slid into the previously empty cell could have left or reached its goal cell.
+    fn update(&self, puzzle: &Puzzle, direction: Direction, previous: u32) -> u32 {
+        let (value, from, to) = moved_tile(puzzle, direction);
+        let goal = puzzle.correct_place(value);
+        previous + (to != goal) as u32 - (from != goal) as u32
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+}
+
+impl Heuristic for Manhattan {
+    fn estimate(&self, puzzle: &Puzzle) -> u32 {
+        puzzle.manhattan_metric()
+    }
+
+    /// Only the tile which slid into the previously empty cell got a step closer to, or further from, its goal cell.
+    fn update(&self, puzzle: &Puzzle, direction: Direction, previous: u32) -> u32 {
+        let (value, from, to) = moved_tile(puzzle, direction);
+        let goal = puzzle.correct_place(value);
+        previous + distance(to, goal) - distance(from, goal)
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+}
+
+impl Heuristic for LinearConflict {
+    fn estimate(&self, puzzle: &Puzzle) -> u32 {
+        puzzle.linear_conflict_metric()
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the tile which the move slid into the previously empty cell,
+/// together with the coordinates (x, y) of the cells it slid from and to.
+fn moved_tile(puzzle: &Puzzle, direction: Direction) -> (Tile, (usize, usize), (usize, usize)) {
+    let (y, x) = puzzle.empty_position();
+    let to = match direction {
+        Direction::Up => (x, y + 1),
+        Direction::Down => (x, y - 1),
+        Direction::Left => (x + 1, y),
+        Direction::Right => (x - 1, y),
+        Direction::None => panic!("Move has to have a direction"),
+    };
+    (puzzle.tile(to.0, to.1), (x, y), to)
+}
+
+fn distance(a: (usize, usize), b: (usize, usize)) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32
+}
+
+/// Walks randomly over boards with the given dimensions and checks that after every move
+/// the updated estimate is the same as the estimate of the whole board.
+#[cfg(test)]
+pub(crate) fn check_updates(heuristic: &dyn Heuristic, width: usize, height: usize, seed: u64) {
+    use crate::rng::Rng;
+
+    let directions = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+    let mut rng = Rng::new(seed);
+    for _ in 0..20 {
+        let mut puzzle = Puzzle::random_solvable(width, height, &mut rng).unwrap();
+        let mut estimate = heuristic.estimate(&puzzle);
+        for _ in 0..200 {
+            let direction = directions[rng.below(directions.len())];
+            let Some(child) = puzzle.slide_empty(&direction) else {
+                continue;
+            };
+
+            let updated = heuristic.update(&child, direction, estimate);
+            estimate = heuristic.estimate(&child);
+            assert_eq!(
+                updated, estimate,
+                "{:?} after moving {:?} on\n{}",
+                heuristic, direction, puzzle
+            );
+            puzzle = child;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hamming_updates_match_estimates() {
+        check_updates(&Hamming, 3, 3, 1);
+        check_updates(&Hamming, 4, 4, 2);
+        check_updates(&Hamming, 5, 3, 3);
+    }
+
+    #[test]
+    fn manhattan_updates_match_estimates() {
+        check_updates(&Manhattan, 3, 3, 1);
+        check_updates(&Manhattan, 4, 4, 2);
+        check_updates(&Manhattan, 5, 3, 3);
+    }
+
+    #[test]
+    fn linear_conflict_updates_match_estimates() {
+        check_updates(&LinearConflict, 3, 3, 1);
+        check_updates(&LinearConflict, 4, 4, 2);
+        check_updates(&LinearConflict, 5, 3, 3);
+    }
+}
@@ -0,0 +1,37 @@
+//! Solver of sliding puzzles, the 15-puzzle and boards of any other size.
+//!
+//! A puzzle is read with [`Puzzle::from_file`] or made with [`Puzzle::from_tiles`],
+//! and solved with [`Puzzle::solve`] using one of the built-in solvers, like [`Bfs`] or [`AStar`],
+//! or any other [`Solver`] implementing a new algorithm.
+//! [`Registry`] makes solvers by their names, the way the command line chooses them.
+//! [`Puzzle::solve_observed`] also reports events of the search to a [`SearchObserver`],
+//! and [`SearchRun`] runs BFS, DFS and A* one expansion at a time.
+//! Informed strategies take any [`Heuristic`], either one of the built-in ones or a custom one.
+
+mod grid;
+mod heuristic;
+mod links;
+mod observer;
+mod pdb;
+mod puzzle;
+mod registry;
+mod rng;
+mod search_run;
+mod solver;
+mod walking_distance;
+
+pub use grid::Tile;
+pub use heuristic::{Hamming, Heuristic, LinearConflict, Manhattan};
+pub use observer::{NoObserver, SearchObserver};
+pub use pdb::{PatternDatabase, PdbError};
+pub use puzzle::{
+    BidirectionalStats, BoardError, Direction, FileReadError, Limit, Order, Outcome, Puzzle,
+    SolveOptions, SolveResult, Weight, DEFAULT_DFS_DEPTH,
+};
+pub use registry::{
+    Create, Registration, Registry, RegistryError, Setting, SolverSettings, DEFAULT_PDB_FILE,
+};
+pub use rng::Rng;
+pub use search_run::{SearchRun, Stepwise};
+pub use solver::{AStar, Bfs, BidirectionalBfs, Dfs, IdaStar, Iddfs, Solver};
+pub use walking_distance::WalkingDistance;
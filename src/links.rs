@@ -0,0 +1,78 @@
+use crate::grid::Grid;
+use crate::puzzle::{Direction, Puzzle};
+use std::collections::HashMap;
+use std::mem::size_of;
+
+/// How a search reached a state, kept instead of the whole path to it.
+/// Following the last moves back from any state leads to the start of the search.
+#[derive(Clone, Copy)]
+pub(crate) struct Link {
+    /// Length of the path to the state.
+    pub depth: u32,
+    /// Move which led to the state, `Direction::None` for the start of the search.
+    pub last_move: Direction,
+}
+
+/// Links of every state a search has reached, keyed by their grids.
+/// A link is only ever replaced by one with a shorter path, so following them always ends at the start.
+pub(crate) type Links = HashMap<Grid, Link>;
+
+/// Returns the moves leading from the start of the search to the state, which has to have a link.
+pub(crate) fn trace_path(links: &Links, state: &Puzzle) -> Vec<Direction> {
+    let mut path = Vec::new();
+    let mut state = state.clone();
+    loop {
+        let link = links[state.grid()];
+        if link.last_move == Direction::None {
+            break;
+        }
+        path.push(link.last_move);
+        state = state.slide_empty(&link.last_move.opposite()).unwrap();
+    }
+
+    path.reverse();
+    path
+}
+
+/// Returns approximate number of bytes taken by the links, whose grids are as large as the sample.
+/// The whole table counts, including the room it has allocated for states which aren't there yet.
+pub(crate) fn links_size(links: &Links, sample: &Grid) -> usize {
+    // The table keeps about 8 buckets for every 7 entries it has room for, each with a control byte.
+    links.capacity() * 8 / 7 * (size_of::<(Grid, Link)>() + 1) + links.len() * sample.heap_size()
+}
+
+/// Returns approximate number of bytes taken by a queue of `T` with the given capacity and length,
+/// whose grids are as large as the sample.
+pub(crate) fn queue_size<T>(capacity: usize, len: usize, sample: &Grid) -> usize {
+    capacity * size_of::<T>() + len * sample.heap_size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_of_links_counts_allocated_room() {
+        let sample = Grid::new((0..9).collect());
+        let mut links = Links::new();
+        assert_eq!(links_size(&links, &sample), 0);
+
+        links.reserve(1000);
+        let entry_size = size_of::<(Grid, Link)>() + 1;
+        assert!(links_size(&links, &sample) >= 1000 * entry_size);
+
+        // Grids of larger boards are on the heap, which counts for every stored one.
+        let large = Grid::new((0..25).collect());
+        links.insert(
+            large.clone(),
+            Link {
+                depth: 0,
+                last_move: Direction::None,
+            },
+        );
+        assert_eq!(
+            links_size(&links, &large),
+            links_size(&links, &sample) + large.heap_size()
+        );
+    }
+}
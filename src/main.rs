@@ -1,104 +1,626 @@
-use puzzle::{Direction, Metric, Puzzle, Strategy};
+use batch::BatchRecord;
+use fifteen_puzzle::{
+    BoardError, Direction, FileReadError, Outcome, PatternDatabase, PdbError, Puzzle, Registry,
+    RegistryError, Rng, Setting, SolveOptions, SolveResult, Solver, SolverSettings,
+    DEFAULT_PDB_FILE,
+};
 use std::env;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-mod puzzle;
+mod batch;
 
+#[derive(Debug)]
 enum ArgsError {
     NotEnoughArguments,
     InvalidStrategy,
-    InvalidOrder,
+    InvalidOption(String),
+    /// The option is valid, but doesn't apply to the command it was given to.
+    UnusedOption(String),
+    Registry(RegistryError),
 }
 
 #[derive(Debug)]
 struct Config {
-    pub strategy: Strategy,
+    pub strategy: Arc<dyn Solver>,
+    /// Name of the strategy and its parameter, like `astr manh`.
+    pub strategy_name: String,
     pub input_file: String,
     pub solution_file: String,
     pub stats_file: String,
+    pub optimal_length: Option<usize>,
+    pub solve_options: SolveOptions,
+    /// Settings the strategy was created with.
+    pub settings: SolverSettings,
 }
 
 impl Config {
     pub fn new(args: &[String]) -> Result<Config, ArgsError> {
-        if args.len() < 5 {
+        if args.len() < 6 {
             return Err(ArgsError::NotEnoughArguments);
         }
 
-        let strategy = args[1].as_str();
-        let order = args[2].as_str();
-
         let input_file = args[3].clone();
         let solution_file = args[4].clone();
         let stats_file = args[5].clone();
 
-        let strategy = match strategy {
-            "bfs" | "dfs" => {
-                let mut directions = [Direction::Up; 4];
-                for (i, direction) in order.to_uppercase().chars().enumerate() {
-                    match direction {
-                        'U' => directions[i] = Direction::Up,
-                        'D' => directions[i] = Direction::Down,
-                        'L' => directions[i] = Direction::Left,
-                        'R' => directions[i] = Direction::Right,
-                        _ => return Err(ArgsError::InvalidOrder),
-                    }
-                }
-                if strategy == "bfs" {
-                    Strategy::Bfs(directions)
-                } else {
-                    Strategy::Dfs(directions)
-                }
-            }
-            "astr" => {
-                let metric = match order {
-                    "manh" => Metric::Manhattan,
-                    "hamm" => Metric::Hamming,
-                    _ => return Err(ArgsError::InvalidOrder),
-                };
-                Strategy::AStar(metric)
-            }
-            _ => return Err(ArgsError::InvalidStrategy),
-        };
+        // Optional flags come after the positional arguments.
+        let mut options = StrategyOptions::new(&args[6..])?;
+        seed_random_orders(&[(&args[1], &args[2])], &mut options.settings);
+        let strategy = Registry::builtin()
+            .create(&args[1], &args[2], &options.settings)
+            .map_err(ArgsError::Registry)?;
 
         Ok(Config {
             strategy,
+            strategy_name: format!("{} {}", args[1], args[2]),
             input_file,
             solution_file,
             stats_file,
+            optimal_length: options.optimal_length,
+            solve_options: options.solve_options,
+            settings: options.settings,
         })
     }
 }
 
-fn main() {
-    // Get the arguments from the command line and parse them into the config.
-    let args: Vec<String> = env::args().collect();
-    let config = Config::new(&args).unwrap_or_else(|err| {
-        print!("Problem parsing arguments: ");
-        match err {
-            ArgsError::NotEnoughArguments => println!("Not enough arguments"),
-            ArgsError::InvalidStrategy => println!("Invalid strategy"),
-            ArgsError::InvalidOrder => println!("Invalid order"),
+/// Flags which tune strategies, given after the positional arguments.
+struct StrategyOptions {
+    pub settings: SolverSettings,
+    /// Known length of the shortest solution, which the found one is compared to.
+    pub optimal_length: Option<usize>,
+    pub solve_options: SolveOptions,
+}
+
+impl StrategyOptions {
+    pub fn new(args: &[String]) -> Result<StrategyOptions, ArgsError> {
+        let mut settings = SolverSettings::default();
+        let mut optimal_length = None;
+        let mut solve_options = SolveOptions::default();
+        let mut options = args.iter();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "--depth" => {
+                    settings.depth_limit = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                "--seed" => {
+                    settings.seed = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                "--pdb" => {
+                    settings.pdb_file = Some(
+                        options
+                            .next()
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?
+                            .clone(),
+                    );
+                }
+                "--weight" => {
+                    settings.weight = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .filter(|weight: &f64| weight.is_finite() && *weight >= 0.0)
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                "--optimal" => {
+                    optimal_length = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .filter(|length| *length > 0)
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                // Given in megabytes.
+                "--memory-limit" => {
+                    let megabytes: usize = options
+                        .next()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?;
+                    solve_options.memory_limit = Some(megabytes * 1024 * 1024);
+                }
+                // Given in seconds, which can have a fraction.
+                "--time-limit" => {
+                    solve_options.time_limit = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                "--processed-limit" => {
+                    solve_options.processed_limit = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                "--visited-limit" => {
+                    solve_options.visited_limit = Some(
+                        options
+                            .next()
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| ArgsError::InvalidOption(option.clone()))?,
+                    );
+                }
+                _ => return Err(ArgsError::InvalidOption(option.clone())),
+            }
+        }
+
+        Ok(StrategyOptions {
+            settings,
+            optimal_length,
+            solve_options,
+        })
+    }
+}
+
+/// Prints why the arguments couldn't be parsed.
+fn print_args_error(err: &ArgsError) {
+    print!("Problem parsing arguments: ");
+    match err {
+        ArgsError::NotEnoughArguments => println!("Not enough arguments"),
+        ArgsError::InvalidStrategy => println!("Invalid strategy"),
+        ArgsError::InvalidOption(option) => println!("Invalid option: {}", option),
+        ArgsError::UnusedOption(option) => println!("Option {} doesn't apply here", option),
+        ArgsError::Registry(RegistryError::UnknownSolver(name)) => {
+            println!("Invalid strategy: {}", name)
+        }
+        ArgsError::Registry(RegistryError::DuplicateName(name)) => {
+            println!("Strategy {} is registered more than once", name)
+        }
+        ArgsError::Registry(RegistryError::InvalidParameter(parameter)) => {
+            println!("Invalid order or metric: {}", parameter)
+        }
+        ArgsError::Registry(RegistryError::UnusedSetting(setting)) => {
+            println!("Option --{} doesn't apply to this strategy", setting)
+        }
+        ArgsError::Registry(RegistryError::PatternDatabase(file, err)) => {
+            print_pdb_error(file, err)
+        }
+    }
+}
+
+/// Prints why a puzzle couldn't be read from a file.
+fn print_file_error(file: &str, err: &FileReadError) {
+    match err {
+        FileReadError::NotFound => {
+            println!("File not found: {}", file);
+        }
+        FileReadError::IsEmpty => {
+            println!("File is empty: {}", file);
+        }
+        FileReadError::IsCorrupt => {
+            println!("File is corrupted: {}", file);
+        }
+        FileReadError::TooLarge => {
+            println!("Puzzle is too large: {}", file);
+        }
+        FileReadError::InvalidValue { line, column } => {
+            println!(
+                "Invalid value at line {}, column {}: {}",
+                line, column, file
+            );
+        }
+        FileReadError::ValueOutOfRange {
+            line,
+            column,
+            value,
+        } => {
+            println!(
+                "Value {} at line {}, column {} doesn't fit on the board: {}",
+                value, line, column, file
+            );
+        }
+        FileReadError::DuplicateValue {
+            line,
+            column,
+            value,
+        } => {
+            println!(
+                "Value {} at line {}, column {} appears more than once: {}",
+                value, line, column, file
+            );
+        }
+        FileReadError::MissingValues {
+            line,
+            expected,
+            found,
+        } => {
+            println!(
+                "Line {} has {} values instead of {}: {}",
+                line, found, expected, file
+            );
+        }
+        FileReadError::ExtraValue { line, column } => {
+            println!(
+                "Line {} has too many values, starting at column {}: {}",
+                line, column, file
+            );
+        }
+        FileReadError::MissingRows { expected, found } => {
+            println!("File has {} rows instead of {}: {}", found, expected, file);
+        }
+        FileReadError::ExtraRow { line } => {
+            println!("Line {} is past the last row of the board: {}", line, file);
+        }
+    }
+}
+
+/// Returns whether the solver can solve the puzzle, printing why if it can't.
+/// Pattern databases tell which board they were built for, the file being the one given in the settings.
+fn check_board(
+    name: &str,
+    solver: &dyn Solver,
+    settings: &SolverSettings,
+    puzzle: &Puzzle,
+) -> bool {
+    let database = solver
+        .heuristic()
+        .and_then(|heuristic| heuristic.pattern_database());
+    if let Some(Err(err)) =
+        database.map(|database| database.check_size(puzzle.width(), puzzle.height()))
+    {
+        let file = settings.pdb_file.as_deref().unwrap_or(DEFAULT_PDB_FILE);
+        print_pdb_error(file, &err);
+        return false;
+    }
+
+    if !solver.supports(puzzle.width(), puzzle.height()) {
+        println!(
+            "Strategy {} doesn't support {}x{} boards",
+            name,
+            puzzle.width(),
+            puzzle.height()
+        );
+        return false;
+    }
+
+    true
+}
+
+/// Prints why a pattern database couldn't be read, written or used.
+fn print_pdb_error(file: &str, err: &PdbError) {
+    match err {
+        PdbError::NotFound => println!("Pattern database not found: {}", file),
+        PdbError::IsCorrupt => println!("Pattern database is corrupted: {}", file),
+        PdbError::WriteFailed => println!("Error writing pattern database to file: {}", file),
+        PdbError::InvalidPartition => {
+            println!("Partition has to split all tiles into non-empty groups")
+        }
+        PdbError::SizeMismatch { expected, found } => println!(
+            "Pattern database {} was built for {}x{} boards, but the puzzle is {}x{}",
+            file, expected.0, expected.1, found.0, found.1
+        ),
+        PdbError::GroupTooLarge(size) => {
+            println!("Groups of {} tiles are too large for this board", size)
         }
+    }
+}
+
+/// Builds pattern databases for `astr pdb`.
+/// Usage: build-pdb <width> <height> <output file> [partition, e.g. 6-6-3]
+fn build_pdb(args: &[String]) {
+    let width = args.first().and_then(|value| value.parse::<usize>().ok());
+    let height = args.get(1).and_then(|value| value.parse::<usize>().ok());
+    let output_file = args.get(2);
+    let partition: Option<Vec<usize>> = args
+        .get(3)
+        .map_or("6-6-3", String::as_str)
+        .split('-')
+        .map(|size| size.parse().ok())
+        .collect();
+
+    let (Some(width), Some(height), Some(output_file), Some(partition)) =
+        (width, height, output_file, partition)
+    else {
+        println!("Usage: build-pdb <width> <height> <output file> [partition, e.g. 6-6-3]");
         std::process::exit(1);
-    });
+    };
 
-    let puzzle = Puzzle::from_file(&config.input_file).unwrap_or_else(|err| {
-        match err {
-            puzzle::FileReadError::NotFound => {
-                println!("File not found: {}", config.input_file);
+    PatternDatabase::build(width, height, &partition)
+        .and_then(|database| database.save(output_file))
+        .unwrap_or_else(|err| {
+            print_pdb_error(output_file, &err);
+            std::process::exit(1);
+        });
+}
+
+/// Options of the `generate` command.
+struct GenerateConfig {
+    width: usize,
+    height: usize,
+    output_dir: String,
+    /// Number of scrambling moves, or none for random solvable puzzles.
+    moves: Option<usize>,
+    count: usize,
+    /// Seed of the generator, or none to take one from the current time.
+    seed: Option<u64>,
+}
+
+impl GenerateConfig {
+    /// Returns none if the arguments are missing or invalid.
+    fn new(args: &[String]) -> Option<GenerateConfig> {
+        let width = args.first()?.parse().ok()?;
+        let height = args.get(1)?.parse().ok()?;
+        let output_dir = args.get(2)?.clone();
+
+        let mut moves = None;
+        let mut random = false;
+        let mut count = 1;
+        let mut seed = None;
+        let mut options = args[3..].iter();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "--random" => random = true,
+                "--moves" => moves = Some(options.next()?.parse().ok()?),
+                "--count" => count = options.next()?.parse().ok()?,
+                "--seed" => seed = Some(options.next()?.parse().ok()?),
+                _ => return None,
             }
-            puzzle::FileReadError::IsEmpty => {
-                println!("File is empty: {}", config.input_file);
+        }
+
+        // Exactly one of the modes has to be chosen.
+        if random == moves.is_some() {
+            return None;
+        }
+
+        Some(GenerateConfig {
+            width,
+            height,
+            output_dir,
+            moves,
+            count,
+            seed,
+        })
+    }
+}
+
+/// Writes scrambled puzzles to files named `<height>x<width>_<moves>_<index>.txt`,
+/// or `<height>x<width>_rnd_<index>.txt` for random ones.
+/// Usage: generate <width> <height> <output directory> (--moves N | --random) [--count N] [--seed N]
+fn generate(args: &[String]) {
+    let config = GenerateConfig::new(args).unwrap_or_else(|| {
+        println!("Usage: generate <width> <height> <output directory> (--moves N | --random) [--count N] [--seed N]");
+        std::process::exit(1);
+    });
+
+    std::fs::create_dir_all(&config.output_dir)
+        .unwrap_or_else(|_| panic!("Error creating directory: {}", config.output_dir));
+
+    // Seeds taken from the time are printed, so that the puzzles can be generated again.
+    let seed = config.seed.unwrap_or_else(|| {
+        let seed = Rng::time_seed();
+        eprintln!(
+            "Generating with seed {} (pass --seed {} to repeat)",
+            seed, seed
+        );
+        seed
+    });
+    let mut rng = Rng::new(seed);
+    for index in 1..=config.count {
+        let (puzzle, kind) = match config.moves {
+            Some(moves) => (
+                Puzzle::scrambled(config.width, config.height, moves, &mut rng),
+                format!("{:02}", moves),
+            ),
+            None => (
+                Puzzle::random_solvable(config.width, config.height, &mut rng),
+                "rnd".to_string(),
+            ),
+        };
+        let puzzle = puzzle.unwrap_or_else(|err| {
+            match err {
+                BoardError::TooSmall => println!("Board has to be at least 2x2"),
+                _ => println!("Invalid board size: {}x{}", config.width, config.height),
             }
-            puzzle::FileReadError::IsCorrupt => {
-                println!("File is corrupted: {}", config.input_file);
+            std::process::exit(1);
+        });
+
+        let file_name = format!(
+            "{}x{}_{}_{:05}.txt",
+            config.height, config.width, kind, index
+        );
+        let file = Path::new(&config.output_dir).join(file_name);
+        let file = file.to_string_lossy();
+        puzzle
+            .to_file(&file)
+            .unwrap_or_else(|_| panic!("Error writing puzzle to file: {}", file));
+    }
+}
+
+/// Gives random orders among the strategies (names with their orders or metrics) a seed taken from
+/// the current time, if none was given. The seed is printed on stderr, so that the run can be repeated.
+fn seed_random_orders(strategies: &[(&str, &str)], settings: &mut SolverSettings) {
+    let registry = Registry::builtin();
+    let random = strategies.iter().any(|&(name, order)| {
+        order.eq_ignore_ascii_case("R")
+            && registry
+                .find(name)
+                .is_some_and(|registration| registration.settings.contains(&Setting::Seed))
+    });
+    if random && settings.seed.is_none() {
+        let seed = Rng::time_seed();
+        eprintln!(
+            "Random orders use seed {} (pass --seed {} to repeat)",
+            seed, seed
+        );
+        settings.seed = Some(seed);
+    }
+}
+
+/// Strategy of a batch, together with its name and order (or metric) for the report.
+type BatchStrategy<'a> = (&'a str, &'a str, Arc<dyn Solver>);
+
+/// Returns the strategies of a batch, given like `bfs:LUDR,astr:manh`.
+/// The options are shared, so every strategy only gets the ones it takes with its order or metric
+/// (a seed only goes to random orders, a pattern database file only to `pdb`),
+/// but an option which none of them takes is an error.
+fn batch_strategies<'a>(
+    specs: &'a str,
+    settings: &SolverSettings,
+) -> Result<Vec<BatchStrategy<'a>>, ArgsError> {
+    let registry = Registry::builtin();
+    let mut strategies = Vec::new();
+    let mut used = Vec::new();
+    for spec in specs.split(',') {
+        let (name, order) = spec.split_once(':').ok_or(ArgsError::InvalidStrategy)?;
+        let registration = registry
+            .find(name)
+            .ok_or_else(|| ArgsError::Registry(RegistryError::UnknownSolver(name.to_string())))?;
+        // Settings the parameter doesn't use are left out one by one, until the rest are accepted.
+        let mut taken = registration.settings.to_vec();
+        let strategy = loop {
+            match registry.create(name, order, &settings.only(&taken)) {
+                Ok(strategy) => break strategy,
+                Err(RegistryError::UnusedSetting(setting)) if taken.contains(&setting) => {
+                    taken.retain(|&other| other != setting);
+                }
+                Err(err) => return Err(ArgsError::Registry(err)),
             }
+        };
+        used.extend(taken);
+        strategies.push((name, order, strategy));
+    }
+
+    if let Some(setting) = settings
+        .given()
+        .into_iter()
+        .find(|setting| !used.contains(setting))
+    {
+        return Err(ArgsError::UnusedOption(format!("--{}", setting)));
+    }
+    Ok(strategies)
+}
+
+/// Solves every puzzle matching the pattern with every strategy, and writes a summary report.
+/// Puzzle and strategy pairs are spread over `--jobs` threads, but the report keeps their order.
+/// Usage: batch <directory or glob> <strategies, e.g. bfs:LUDR,astr:manh> <report file (.csv or .json)> [--jobs N] [options]
+fn batch(args: &[String]) {
+    if args.len() < 3 {
+        println!("Usage: batch <directory or glob> <strategies, e.g. bfs:LUDR,astr:manh> <report file (.csv or .json)> [--jobs N] [options]");
+        std::process::exit(1);
+    }
+
+    let pattern = &args[0];
+    let report_file = &args[2];
+
+    // Number of threads only matters to the batch, the rest of the options tune the strategies.
+    let mut jobs = 1;
+    let mut strategy_args = Vec::new();
+    let mut options = args[3..].iter();
+    while let Some(option) = options.next() {
+        if option == "--jobs" {
+            jobs = options
+                .next()
+                .and_then(|value| value.parse().ok())
+                .filter(|jobs| *jobs > 0)
+                .unwrap_or_else(|| {
+                    print_args_error(&ArgsError::InvalidOption(option.clone()));
+                    std::process::exit(1);
+                });
+        } else {
+            strategy_args.push(option.clone());
         }
+    }
+
+    let mut options = StrategyOptions::new(&strategy_args).unwrap_or_else(|err| {
+        print_args_error(&err);
         std::process::exit(1);
     });
+    let specs: Vec<(&str, &str)> = args[1]
+        .split(',')
+        .filter_map(|spec| spec.split_once(':'))
+        .collect();
+    seed_random_orders(&specs, &mut options.settings);
+
+    // Solutions of many puzzles aren't compared to a single optimal length.
+    if options.optimal_length.is_some() {
+        print_args_error(&ArgsError::UnusedOption("--optimal".to_string()));
+        std::process::exit(1);
+    }
+
+    let strategies = batch_strategies(&args[1], &options.settings).unwrap_or_else(|err| {
+        print_args_error(&err);
+        std::process::exit(1);
+    });
+
+    let files = batch::find_puzzles(pattern).unwrap_or_else(|_| {
+        println!("Error listing puzzle files: {}", pattern);
+        std::process::exit(1);
+    });
+
+    // Puzzles are read and checked up front, so that only the solving runs in parallel.
+    let mut puzzles = Vec::new();
+    let mut pairs = Vec::new();
+    for file in files {
+        let file_name = file.to_string_lossy();
+        // Puzzles which can't be read are reported and left out of the summary.
+        let puzzle = match Puzzle::from_file(&file_name) {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                print_file_error(&file_name, &err);
+                continue;
+            }
+        };
+        let puzzle_id = file
+            .file_stem()
+            .map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+
+        for (i, (name, order, strategy)) in strategies.iter().enumerate() {
+            let name = format!("{} {}", name, order);
+            if check_board(&name, strategy.as_ref(), &options.settings, &puzzle) {
+                pairs.push((puzzles.len(), i));
+            }
+        }
+        puzzles.push((puzzle_id, puzzle));
+    }
+
+    let results = batch::run_parallel(&pairs, jobs, |&(puzzle, strategy)| {
+        puzzles[puzzle]
+            .1
+            .solve(strategies[strategy].2.as_ref(), &options.solve_options)
+    });
+
+    let records: Vec<BatchRecord> = pairs
+        .iter()
+        .zip(results)
+        .map(|(&(puzzle, strategy), result)| {
+            let (name, order, _) = &strategies[strategy];
+            BatchRecord {
+                puzzle: puzzles[puzzle].0.clone(),
+                strategy: name.to_string(),
+                parameter: order.to_string(),
+                result,
+            }
+        })
+        .collect();
 
-    let solution = puzzle.solve(&config.strategy);
+    batch::write_report(report_file, &records)
+        .unwrap_or_else(|_| panic!("Error writing report to file: {}", report_file));
+}
 
-    let solution_file_content = match &solution.path {
+/// Returns contents of the solution file: length of the solution, and its moves on the next line.
+/// If there is no solution, the length is -1 and the next line tells why, as one of
+/// `proven_unsolvable_by_parity` (shown without searching), `proven_unsolvable` (every reachable state was searched),
+/// `depth_limit_exhausted` or `resource_limit_hit:<limit>`,
+/// where the limit is `memory`, `time`, `processed_states` or `visited_states`.
+fn solution_file_content(solution: &SolveResult) -> String {
+    match &solution.path {
         Some(path) => {
             let mut steps = String::new();
             for step in path {
@@ -112,15 +634,22 @@ fn main() {
             }
             format!("{}\n{}", &path.len(), steps)
         }
-        None => String::from("-1"),
-    };
+        None => format!("-1\n{}", solution.outcome),
+    }
+}
 
-    let path_len = match solution.path {
+/// Returns contents of the stats file, a value per line: length of the solution (-1 if there is none),
+/// visited states, processed states, max depth and time spent in milliseconds.
+/// Some strategies add their own values after these, followed by how many times longer the solution is
+/// than the optimal one, if its length was given. The last line is always the outcome,
+/// `solved` or one of the reasons written to the solution file.
+fn stats_file_content(solution: &SolveResult, optimal_length: Option<usize>) -> String {
+    let path_len = match &solution.path {
         Some(path) => path.len().to_string(),
         None => "-1".to_string(),
     };
 
-    let stats_file_content = format!(
+    let mut stats_file_content = format!(
         "{}\n{}\n{}\n{}\n{:.3}",
         path_len,
         solution.visited_states,
@@ -129,9 +658,269 @@ fn main() {
         solution.time_spent as f32 * 10.0_f32.powi(-6)
     );
 
+    // Iterative strategies additionally report how many passes they made, and processed states of each pass.
+    if !solution.iteration_states.is_empty() {
+        stats_file_content.push_str(&format!("\n{}", solution.iterations));
+        for states in &solution.iteration_states {
+            stats_file_content.push_str(&format!("\n{}", states));
+        }
+    }
+
+    // Bidirectional search additionally reports visited and processed states of each direction.
+    if let Some(stats) = &solution.bidirectional {
+        stats_file_content.push_str(&format!(
+            "\n{}\n{}\n{}\n{}",
+            stats.forward_visited_states,
+            stats.forward_processed_states,
+            stats.backward_visited_states,
+            stats.backward_processed_states
+        ));
+    }
+
+    if let (Some(path), Some(optimal_length)) = (&solution.path, optimal_length) {
+        stats_file_content.push_str(&format!(
+            "\n{:.3}",
+            path.len() as f64 / optimal_length as f64
+        ));
+    }
+
+    stats_file_content.push_str(&format!("\n{}", solution.outcome));
+    stats_file_content
+}
+
+/// Replays a solution file on a puzzle and reports whether it solves it.
+/// Exits with 0 if the solution is correct, 2 if it isn't, and 1 if the files can't be read.
+/// Usage: verify <puzzle file> <solution file>
+fn verify(args: &[String]) {
+    let (Some(puzzle_file), Some(solution_file)) = (args.first(), args.get(1)) else {
+        println!("Usage: verify <puzzle file> <solution file>");
+        std::process::exit(1);
+    };
+
+    let puzzle = Puzzle::from_file(puzzle_file).unwrap_or_else(|err| {
+        print_file_error(puzzle_file, &err);
+        std::process::exit(1);
+    });
+
+    let solution = std::fs::read_to_string(solution_file).unwrap_or_else(|_| {
+        println!("File not found: {}", solution_file);
+        std::process::exit(1);
+    });
+    let mut lines = solution.lines();
+    let stated_length: i64 = lines
+        .next()
+        .and_then(|line| line.trim().parse().ok())
+        .unwrap_or_else(|| {
+            println!(
+                "Solution file has no valid length on its first line: {}",
+                solution_file
+            );
+            std::process::exit(1);
+        });
+    let moves = lines.next().unwrap_or("").trim();
+
+    // No solution is only correct if there really is none.
+    if stated_length == -1 {
+        if puzzle.is_solvable() {
+            println!("Solution file has no solution, but the puzzle is solvable");
+            std::process::exit(2);
+        }
+        println!("Puzzle is unsolvable, as stated");
+        return;
+    }
+
+    let mut correct = true;
+    let mut state = puzzle;
+    for (i, letter) in moves.chars().enumerate() {
+        let next_state =
+            Direction::from_letter(letter).and_then(|direction| state.move_empty(&direction));
+        match next_state {
+            Some(next_state) => state = next_state,
+            None => {
+                println!("Move {} ({}) is illegal", i + 1, letter);
+                correct = false;
+                break;
+            }
+        }
+    }
+
+    let length = moves.chars().count();
+    if stated_length != length as i64 {
+        println!(
+            "Stated length {} doesn't match the {} moves given",
+            stated_length, length
+        );
+        correct = false;
+    }
+
+    if state.is_solved() {
+        println!("Board is solved");
+    } else {
+        println!("Board is not solved");
+        correct = false;
+    }
+
+    if !correct {
+        std::process::exit(2);
+    }
+}
+
+/// Prints every strategy which can be chosen on the command line, with its parameter and flags.
+/// Usage: list-strategies
+fn list_strategies() {
+    let registry = Registry::builtin();
+    let name_width = registry
+        .registrations()
+        .iter()
+        .map(|registration| registration.name.len())
+        .max()
+        .unwrap_or(0);
+    for registration in registry.registrations() {
+        println!(
+            "{:<width$}  {}",
+            registration.name,
+            registration.description,
+            width = name_width
+        );
+        println!(
+            "{:<width$}  parameter: {}",
+            "",
+            registration.parameter,
+            width = name_width
+        );
+        if !registration.settings.is_empty() {
+            let flags: Vec<String> = registration
+                .settings
+                .iter()
+                .map(|setting| format!("--{}", setting))
+                .collect();
+            println!(
+                "{:<width$}  options: {}",
+                "",
+                flags.join(", "),
+                width = name_width
+            );
+        }
+    }
+    println!();
+    println!("Every strategy also takes --memory-limit, --time-limit, --processed-limit, --visited-limit and --optimal.");
+}
+
+fn main() {
+    // Get the arguments from the command line and parse them into the config.
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(String::as_str) {
+        Some("build-pdb") => return build_pdb(&args[2..]),
+        Some("generate") => return generate(&args[2..]),
+        Some("batch") => return batch(&args[2..]),
+        Some("verify") => return verify(&args[2..]),
+        Some("list-strategies") => return list_strategies(),
+        _ => {}
+    }
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        print_args_error(&err);
+        std::process::exit(1);
+    });
+
+    let puzzle = Puzzle::from_file(&config.input_file).unwrap_or_else(|err| {
+        print_file_error(&config.input_file, &err);
+        std::process::exit(1);
+    });
+
+    if !check_board(
+        &config.strategy_name,
+        config.strategy.as_ref(),
+        &config.settings,
+        &puzzle,
+    ) {
+        std::process::exit(1);
+    }
+
+    let solution = puzzle.solve(config.strategy.as_ref(), &config.solve_options);
+
+    match solution.outcome {
+        Outcome::ProvenUnsolvableByParity => {
+            println!("Puzzle is unsolvable, as the parity check shows")
+        }
+        Outcome::ResourceLimitHit(limit) => {
+            println!("Search stopped after reaching a limit: {}", limit)
+        }
+        _ => {}
+    }
+
+    let solution_file_content = solution_file_content(&solution);
+    // Weighted and greedy searches trade solution length for speed, this shows how much they gave up.
+    if let (Some(path), Some(optimal_length)) = (&solution.path, config.optimal_length) {
+        println!(
+            "Solution is {} moves long, {:.3} times as long as the optimal {}",
+            path.len(),
+            path.len() as f64 / optimal_length as f64,
+            optimal_length
+        );
+    }
+
+    let stats_file_content = stats_file_content(&solution, config.optimal_length);
+
     std::fs::write(&config.solution_file, solution_file_content)
-        .expect(format!("Error writing solution to file: {}", &config.solution_file).as_str());
+        .unwrap_or_else(|_| panic!("Error writing solution to file: {}", &config.solution_file));
 
     std::fs::write(&config.stats_file, stats_file_content)
-        .expect(format!("Error writing stats to file: {}", &config.stats_file).as_str());
+        .unwrap_or_else(|_| panic!("Error writing stats to file: {}", &config.stats_file));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_options_only_go_to_strategies_which_take_them() {
+        let seeded = SolverSettings {
+            seed: Some(1),
+            ..SolverSettings::default()
+        };
+
+        let strategies = batch_strategies("bfs:LUDR,dfs:R,iddfs:R", &seeded).unwrap();
+        let solvers: Vec<String> = strategies
+            .iter()
+            .map(|(_, _, strategy)| format!("{:?}", strategy))
+            .collect();
+        assert!(solvers[0].contains("Fixed"));
+        assert!(solvers[1].contains("Random(1)"));
+        assert!(solvers[2].contains("Random(1)"));
+
+        // A seed which no strategy takes is still an error.
+        assert!(matches!(
+            batch_strategies("bfs:LUDR,astr:manh", &seeded),
+            Err(ArgsError::UnusedOption(option)) if option == "--seed"
+        ));
+
+        let weighted = SolverSettings {
+            weight: Some(2.0),
+            ..SolverSettings::default()
+        };
+        assert!(batch_strategies("bfs:LUDR,astr:manh", &weighted).is_ok());
+        assert!(matches!(
+            batch_strategies("bfs:LUDR,dfs:R", &weighted),
+            Err(ArgsError::UnusedOption(option)) if option == "--weight"
+        ));
+    }
+
+    #[test]
+    fn random_orders_get_a_seed_when_none_is_given() {
+        let mut settings = SolverSettings::default();
+        seed_random_orders(&[("bfs", "LUDR"), ("astr", "manh")], &mut settings);
+        assert_eq!(settings.seed, None);
+
+        seed_random_orders(&[("bfs", "LUDR"), ("dfs", "r")], &mut settings);
+        assert!(settings.seed.is_some());
+
+        let mut seeded = SolverSettings {
+            seed: Some(4),
+            ..SolverSettings::default()
+        };
+        seed_random_orders(&[("iddfs", "R")], &mut seeded);
+        assert_eq!(seeded.seed, Some(4));
+    }
 }
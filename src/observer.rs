@@ -0,0 +1,180 @@
+use crate::puzzle::Puzzle;
+
+/// Receives events of a search as it runs, for progress reports, custom statistics or tracing.
+/// Every event does nothing by default, so observers only implement the ones they need.
+/// Searches are generic over their observer, so the hooks of [`NoObserver`] compile away entirely.
+pub trait SearchObserver {
+    /// A state was taken from the frontier and its neighbours are about to be generated.
+    fn expanded(&mut self, _state: &Puzzle, _depth: usize) {}
+
+    /// A neighbour of the expanded state was generated, before it's checked against the states seen so far.
+    fn generated(&mut self, _state: &Puzzle, _depth: usize) {}
+
+    /// A generated state was dropped, because it was already reached by a path at least as short.
+    fn duplicate_pruned(&mut self, _state: &Puzzle, _depth: usize) {}
+
+    /// The goal was reached, the depth being the length of the solution.
+    fn goal_found(&mut self, _state: &Puzzle, _depth: usize) {}
+
+    /// The search took a state deeper than any before it.
+    fn depth_increased(&mut self, _depth: usize) {}
+}
+
+/// Lets a search report to an observer which stays with the caller.
+impl<O: SearchObserver + ?Sized> SearchObserver for &mut O {
+    fn expanded(&mut self, state: &Puzzle, depth: usize) {
+        (**self).expanded(state, depth);
+    }
+
+    fn generated(&mut self, state: &Puzzle, depth: usize) {
+        (**self).generated(state, depth);
+    }
+
+    fn duplicate_pruned(&mut self, state: &Puzzle, depth: usize) {
+        (**self).duplicate_pruned(state, depth);
+    }
+
+    fn goal_found(&mut self, state: &Puzzle, depth: usize) {
+        (**self).goal_found(state, depth);
+    }
+
+    fn depth_increased(&mut self, depth: usize) {
+        (**self).depth_increased(depth);
+    }
+}
+
+/// Observer which ignores every event, used when nobody is watching the search.
+pub struct NoObserver;
+
+impl SearchObserver for NoObserver {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::Manhattan;
+    use crate::puzzle::{Order, SolveOptions, Weight, INFORMED_ORDER};
+    use crate::rng::Rng;
+    use crate::solver::{AStar, Bfs, IdaStar};
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Expanded(usize),
+        Generated(usize),
+        Pruned(usize),
+        Goal(usize),
+        Deeper(usize),
+    }
+    use Event::*;
+
+    /// Records the events in the order they're reported, with their depths.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl SearchObserver for Recorder {
+        fn expanded(&mut self, _state: &Puzzle, depth: usize) {
+            self.events.push(Expanded(depth));
+        }
+
+        fn generated(&mut self, _state: &Puzzle, depth: usize) {
+            self.events.push(Generated(depth));
+        }
+
+        fn duplicate_pruned(&mut self, _state: &Puzzle, depth: usize) {
+            self.events.push(Pruned(depth));
+        }
+
+        fn goal_found(&mut self, state: &Puzzle, depth: usize) {
+            assert!(state.is_solved());
+            self.events.push(Goal(depth));
+        }
+
+        fn depth_increased(&mut self, depth: usize) {
+            self.events.push(Deeper(depth));
+        }
+    }
+
+    #[test]
+    fn events_are_reported_in_the_order_they_happen() {
+        // Two moves of the empty cell to the right solve the board.
+        let puzzle = Puzzle::from_tiles(3, 3, &[1, 2, 3, 4, 5, 6, 0, 7, 8]).unwrap();
+        let solver = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+
+        let mut recorder = Recorder::default();
+        puzzle.solve_observed(&solver, &SolveOptions::default(), &mut recorder);
+        assert_eq!(
+            recorder.events,
+            [
+                Expanded(0),
+                Generated(1),
+                Generated(1),
+                Deeper(1),
+                Expanded(1),
+                Generated(2),
+                Generated(2),
+                Deeper(2),
+                Goal(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_agree_with_the_search_result() {
+        let puzzle = Puzzle::scrambled(3, 3, 16, &mut Rng::new(24)).unwrap();
+        let solver = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+
+        let mut recorder = Recorder::default();
+        let result = puzzle.solve_observed(&solver, &SolveOptions::default(), &mut recorder);
+        let events = recorder.events;
+
+        // The goal ends the search and is the only processed state which isn't expanded.
+        let path_len = result.path.unwrap().len();
+        assert_eq!(events.last(), Some(&Goal(path_len)));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, Goal(_)))
+                .count(),
+            1
+        );
+        let expanded = events.iter().filter(|event| matches!(event, Expanded(_)));
+        assert_eq!(expanded.count(), result.processed_states - 1);
+
+        // Only generated states are pruned, right after being generated.
+        assert!(events.iter().any(|event| matches!(event, Pruned(_))));
+        for (i, event) in events.iter().enumerate() {
+            if let Pruned(depth) = event {
+                assert_eq!(events[i - 1], Generated(*depth));
+            }
+        }
+
+        let deeper: Vec<usize> = events
+            .iter()
+            .filter_map(|event| match event {
+                Deeper(depth) => Some(*depth),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deeper, (1..=result.max_depth).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn solvers_which_dont_report_events_run_unobserved() {
+        let puzzle = Puzzle::scrambled(3, 3, 16, &mut Rng::new(24)).unwrap();
+        let solver = IdaStar {
+            heuristic: Arc::new(Manhattan),
+        };
+
+        let mut recorder = Recorder::default();
+        let result = puzzle.solve_observed(&solver, &SolveOptions::default(), &mut recorder);
+        assert!(result.path.is_some());
+        assert!(recorder.events.is_empty());
+    }
+}
@@ -0,0 +1,435 @@
+use crate::heuristic::Heuristic;
+use crate::puzzle::{Direction, Puzzle};
+use std::collections::VecDeque;
+use std::fs;
+
+/// First bytes of every pattern database file.
+const MAGIC: &[u8; 4] = b"FPDB";
+/// Version of the file format, bumped whenever the layout changes.
+const VERSION: u8 = 1;
+/// Marks entries of the search table that haven't been reached yet.
+const UNREACHED: u8 = u8::MAX;
+/// Most entries the search table of a group may have, one byte each.
+/// Also keeps their ranks within `u32`, which the search queue holds.
+const MAX_TABLE_LEN: usize = 1 << 30;
+
+#[derive(Debug)]
+pub enum PdbError {
+    NotFound,
+    IsCorrupt,
+    WriteFailed,
+    /// Sizes of the groups have to be positive and cover every tile exactly once.
+    InvalidPartition,
+    /// The database was built for a board with different dimensions (width, height).
+    SizeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// Building a group of the given size would need a search table larger than `MAX_TABLE_LEN`.
+    GroupTooLarge(usize),
+}
+
+/// Additive disjoint pattern database.
+/// Tiles are split into groups, and for every placement of a group's tiles we store how many moves
+/// of these tiles (ignoring all the others) are needed to put them in place.
+/// Since every move moves a tile of only one group, sum of the values over all groups never overestimates.
+pub struct PatternDatabase {
+    width: usize,
+    height: usize,
+    groups: Vec<PatternGroup>,
+}
+
+struct PatternGroup {
+    /// Values of the tiles in the group.
+    tiles: Vec<u8>,
+    /// Moves needed to solve the group, indexed by rank of the cells its tiles are on.
+    table: Vec<u8>,
+}
+
+impl PatternDatabase {
+    /// Builds a database for the goal of a board with the given dimensions.
+    /// Partition contains sizes of the groups, tiles are assigned to them in order,
+    /// so `[6, 6, 3]` means groups 1-6, 7-12 and 13-15.
+    pub fn build(
+        width: usize,
+        height: usize,
+        partition: &[usize],
+    ) -> Result<PatternDatabase, PdbError> {
+        let cells = width.saturating_mul(height);
+        if cells < 2
+            || cells > u8::MAX as usize
+            || partition.contains(&0)
+            || partition.iter().sum::<usize>() != cells - 1
+        {
+            return Err(PdbError::InvalidPartition);
+        }
+        // Tables of all the groups are checked before building any of them.
+        if let Some(&size) = partition.iter().find(
+            |&&size| !matches!(arrangements(cells, size + 1), Some(len) if len <= MAX_TABLE_LEN),
+        ) {
+            return Err(PdbError::GroupTooLarge(size));
+        }
+
+        // Goal positions of tiles, indexed by their values.
+        let goal = Puzzle::goal(width, height).tile_positions();
+
+        let mut groups = Vec::new();
+        let mut next_tile = 1;
+        for size in partition {
+            let tiles: Vec<u8> = (next_tile..next_tile + size)
+                .map(|tile| tile as u8)
+                .collect();
+            next_tile += size;
+
+            let table = build_table(width, height, &tiles, &goal);
+            groups.push(PatternGroup { tiles, table });
+        }
+
+        Ok(PatternDatabase {
+            width,
+            height,
+            groups,
+        })
+    }
+
+    /// Reads a database saved with `save`.
+    pub fn load(path: &str) -> Result<PatternDatabase, PdbError> {
+        let bytes = fs::read(path).map_err(|_err| PdbError::NotFound)?;
+        let mut reader = Reader { bytes: &bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC || reader.take(1)?[0] != VERSION {
+            return Err(PdbError::IsCorrupt);
+        }
+
+        let width = reader.u32()? as usize;
+        let height = reader.u32()? as usize;
+        let group_count = reader.u32()? as usize;
+        let cells = width.saturating_mul(height);
+        if !(2..=u8::MAX as usize).contains(&cells) {
+            return Err(PdbError::IsCorrupt);
+        }
+
+        // Groups have to be disjoint, so every tile may only be seen once.
+        let mut seen = vec![false; cells];
+        let mut groups = Vec::new();
+        for _ in 0..group_count {
+            let tile_count = reader.u32()? as usize;
+            let tiles = reader.take(tile_count)?.to_vec();
+            if tiles.is_empty() {
+                return Err(PdbError::IsCorrupt);
+            }
+            for &tile in &tiles {
+                if tile == 0 || tile as usize >= cells || seen[tile as usize] {
+                    return Err(PdbError::IsCorrupt);
+                }
+                seen[tile as usize] = true;
+            }
+
+            let table_len = reader.u64()? as usize;
+            if Some(table_len) != arrangements(cells, tile_count) {
+                return Err(PdbError::IsCorrupt);
+            }
+            let table = reader.take(table_len)?.to_vec();
+
+            groups.push(PatternGroup { tiles, table });
+        }
+
+        // Every tile apart from the empty one has to be in a group.
+        if !reader.bytes.is_empty() || seen[1..].contains(&false) {
+            return Err(PdbError::IsCorrupt);
+        }
+
+        Ok(PatternDatabase {
+            width,
+            height,
+            groups,
+        })
+    }
+
+    /// Writes the database to a binary file:
+    /// magic, version, width, height and number of groups, then for every group
+    /// the number of tiles, their values, length of its table and the table itself.
+    /// All numbers are little endian.
+    pub fn save(&self, path: &str) -> Result<(), PdbError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.width as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.groups.len() as u32).to_le_bytes());
+
+        for group in &self.groups {
+            bytes.extend_from_slice(&(group.tiles.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&group.tiles);
+            bytes.extend_from_slice(&(group.table.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&group.table);
+        }
+
+        fs::write(path, bytes).map_err(|_err| PdbError::WriteFailed)
+    }
+
+    /// Returns an error if the database can't be used for a board with given dimensions.
+    pub fn check_size(&self, width: usize, height: usize) -> Result<(), PdbError> {
+        if (self.width, self.height) != (width, height) {
+            return Err(PdbError::SizeMismatch {
+                expected: (self.width, self.height),
+                found: (width, height),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Heuristic for PatternDatabase {
+    /// Returns the sum of the moves needed by every group.
+    fn estimate(&self, puzzle: &Puzzle) -> u32 {
+        let positions = puzzle.tile_positions();
+        let cells = self.width * self.height;
+        let mut score = 0;
+        for group in &self.groups {
+            let group_cells: Vec<usize> = group
+                .tiles
+                .iter()
+                .map(|&tile| positions[tile as usize])
+                .collect();
+            score += group.table[rank(&group_cells, cells)] as u32;
+        }
+        score
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    /// Databases only work for boards of the size they were built for.
+    fn supports(&self, width: usize, height: usize) -> bool {
+        self.check_size(width, height).is_ok()
+    }
+
+    fn pattern_database(&self) -> Option<&PatternDatabase> {
+        Some(self)
+    }
+}
+
+impl std::fmt::Debug for PatternDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let groups: Vec<&Vec<u8>> = self.groups.iter().map(|group| &group.tiles).collect();
+        f.debug_struct("PatternDatabase")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("groups", &groups)
+            .finish()
+    }
+}
+
+/// Runs a backward search from the goal over placements of the group's tiles and the empty cell.
+/// Moving the empty cell onto a tile of the group costs one move, onto any other cell it's free,
+/// so we're using a deque and push free moves to the front (0-1 BFS).
+fn build_table(width: usize, height: usize, tiles: &[u8], goal: &[usize]) -> Vec<u8> {
+    let cells = width * height;
+    let k = tiles.len();
+
+    // The state holds cells of the group's tiles followed by the cell of the empty one.
+    let mut start: Vec<usize> = tiles.iter().map(|&tile| goal[tile as usize]).collect();
+    start.push(goal[0]);
+
+    let mut distances = vec![UNREACHED; arrangements(cells, k + 1).unwrap()];
+    let mut queue = VecDeque::new();
+
+    let start_rank = rank(&start, cells);
+    distances[start_rank] = 0;
+    queue.push_back(start_rank as u32);
+
+    while let Some(state_rank) = queue.pop_front() {
+        let distance = distances[state_rank as usize];
+        let state = unrank(state_rank as usize, k + 1, cells);
+        let empty = state[k];
+
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let Some(target) = neighbour_cell(empty, &direction, width, height) else {
+                continue;
+            };
+
+            let mut next = state.clone();
+            next[k] = target;
+            // If there's a tile of the group on the target cell, it slides into the empty one.
+            let cost = match state[..k].iter().position(|&cell| cell == target) {
+                Some(i) => {
+                    next[i] = empty;
+                    1
+                }
+                None => 0,
+            };
+
+            let next_rank = rank(&next, cells);
+            let next_distance = distance + cost;
+            if next_distance < distances[next_rank] {
+                distances[next_rank] = next_distance;
+                if cost == 0 {
+                    queue.push_front(next_rank as u32);
+                } else {
+                    queue.push_back(next_rank as u32);
+                }
+            }
+        }
+    }
+
+    // The empty cell was placed last when ranking, so every placement of the group's tiles
+    // owns a run of (cells - k) consecutive entries, one for each cell the empty one can be on.
+    // The estimate can't depend on where the empty cell is, so we take the lowest of them.
+    distances
+        .chunks(cells - k)
+        .map(|run| *run.iter().min().unwrap())
+        .collect()
+}
+
+/// Returns the cell next to the given one in the given direction, if it's on the board.
+fn neighbour_cell(
+    cell: usize,
+    direction: &Direction,
+    width: usize,
+    height: usize,
+) -> Option<usize> {
+    let (y, x) = (cell / width, cell % width);
+    match direction {
+        Direction::Up if y > 0 => Some(cell - width),
+        Direction::Down if y < height - 1 => Some(cell + width),
+        Direction::Left if x > 0 => Some(cell - 1),
+        Direction::Right if x < width - 1 => Some(cell + 1),
+        _ => None,
+    }
+}
+
+/// Returns the number of ways to place k distinct tiles on n cells, if it fits in `usize`.
+fn arrangements(n: usize, k: usize) -> Option<usize> {
+    (n - k + 1..=n).try_fold(1usize, |product, factor| product.checked_mul(factor))
+}
+
+/// Returns the index of a placement of distinct cells among all `arrangements(n, cells.len())`.
+/// Every cell is numbered among the ones not taken by the cells before it.
+fn rank(cells: &[usize], n: usize) -> usize {
+    let mut rank = 0;
+    for (i, &cell) in cells.iter().enumerate() {
+        let taken_before = cells[..i].iter().filter(|&&other| other < cell).count();
+        rank = rank * (n - i) + cell - taken_before;
+    }
+    rank
+}
+
+/// Inverse of `rank`.
+fn unrank(mut rank: usize, k: usize, n: usize) -> Vec<usize> {
+    let mut digits = vec![0; k];
+    for i in (0..k).rev() {
+        digits[i] = rank % (n - i);
+        rank /= n - i;
+    }
+
+    let mut taken = vec![false; n];
+    digits
+        .into_iter()
+        .map(|digit| {
+            let cell = (0..n).filter(|&cell| !taken[cell]).nth(digit).unwrap();
+            taken[cell] = true;
+            cell
+        })
+        .collect()
+}
+
+/// Reads consecutive values out of the database file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PdbError> {
+        if self.bytes.len() < len {
+            return Err(PdbError::IsCorrupt);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, PdbError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, PdbError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::check_updates;
+
+    /// Saves a database of a 3x2 board with the given groups and loads it back.
+    fn save_and_load(name: &str, groups: &[&[u8]]) -> Result<PatternDatabase, PdbError> {
+        let database = PatternDatabase {
+            width: 3,
+            height: 2,
+            groups: groups
+                .iter()
+                .map(|tiles| PatternGroup {
+                    tiles: tiles.to_vec(),
+                    table: vec![0; arrangements(6, tiles.len()).unwrap()],
+                })
+                .collect(),
+        };
+        let path = std::env::temp_dir().join(format!("fifteen_puzzle_{}.pdb", name));
+        let path = path.to_str().unwrap();
+        database.save(path).unwrap();
+        let loaded = PatternDatabase::load(path);
+        fs::remove_file(path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn loaded_groups_have_to_cover_every_tile_once() {
+        assert!(save_and_load("split", &[&[1, 2, 3], &[4, 5]]).is_ok());
+        assert!(matches!(
+            save_and_load("overlapping", &[&[1, 2, 3], &[3, 4, 5]]),
+            Err(PdbError::IsCorrupt)
+        ));
+        assert!(matches!(
+            save_and_load("repeated", &[&[1, 1, 2], &[3, 4, 5]]),
+            Err(PdbError::IsCorrupt)
+        ));
+        assert!(matches!(
+            save_and_load("missing", &[&[1, 2], &[4, 5]]),
+            Err(PdbError::IsCorrupt)
+        ));
+        assert!(matches!(
+            save_and_load("empty", &[&[1, 2, 3, 4, 5], &[]]),
+            Err(PdbError::IsCorrupt)
+        ));
+    }
+
+    #[test]
+    fn groups_with_too_large_tables_are_not_built() {
+        assert!(matches!(
+            PatternDatabase::build(4, 4, &[8, 7]),
+            Err(PdbError::GroupTooLarge(8))
+        ));
+        assert!(matches!(
+            PatternDatabase::build(15, 17, &[254]),
+            Err(PdbError::GroupTooLarge(254))
+        ));
+        assert!(matches!(
+            PatternDatabase::build(usize::MAX, 2, &[1]),
+            Err(PdbError::InvalidPartition)
+        ));
+    }
+
+    #[test]
+    fn pattern_database_updates_match_estimates() {
+        check_updates(&PatternDatabase::build(3, 3, &[4, 4]).unwrap(), 3, 3, 1);
+        check_updates(&PatternDatabase::build(3, 3, &[3, 3, 2]).unwrap(), 3, 3, 2);
+    }
+}
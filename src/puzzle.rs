@@ -1,16 +1,85 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet, VecDeque};
+use crate::grid::{Grid, Tile};
+use crate::heuristic::Heuristic;
+use crate::links::{links_size, queue_size, trace_path, Link, Links};
+use crate::observer::SearchObserver;
+use crate::rng::Rng;
+use crate::solver::Solver;
+use crate::walking_distance::Tables;
 use std::fs;
 use std::hash::Hash;
-use std::time::Instant;
-
-const MAX_DEPTH: usize = 20;
+use std::time::{Duration, Instant};
+
+/// Depth limit used by DFS when none is given.
+pub const DEFAULT_DFS_DEPTH: usize = 20;
+
+/// Order in which informed strategies expand neighbours. Since they pick states by their metric, it does not matter.
+pub(crate) const INFORMED_ORDER: [Direction; 4] = [
+    Direction::Left,
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+];
+
+/// Reason why a board couldn't be made from its dimensions and tiles.
+#[derive(Debug, PartialEq)]
+pub enum BoardError {
+    /// Width or height is 0, or the board has more cells than tiles can be numbered.
+    InvalidSize,
+    /// The board is narrower or lower than 2 cells, so it can't be scrambled or shuffled.
+    TooSmall,
+    /// Number of tiles isn't the number of cells of the board.
+    WrongTileCount { expected: usize, found: usize },
+    /// A tile doesn't fit on the board (it's not lower than the number of cells). Index is in row-major order.
+    ValueOutOfRange { index: usize, value: Tile },
+    /// A tile appears for the second time. Since 0 is the empty cell, there can only be one of it too.
+    DuplicateValue { index: usize, value: Tile },
+}
 
 #[derive(Debug)]
 pub enum FileReadError {
     NotFound,
     IsEmpty,
+    /// The first line doesn't hold exactly two positive dimensions.
     IsCorrupt,
+    /// The board has more cells than tiles can be numbered.
+    TooLarge,
+    /// Lines and columns are counted from 1, columns in characters.
+    InvalidValue {
+        line: usize,
+        column: usize,
+    },
+    /// A value doesn't fit on the board (it's not lower than the number of cells).
+    ValueOutOfRange {
+        line: usize,
+        column: usize,
+        value: usize,
+    },
+    /// A value appears for the second time. Since 0 is the empty cell, there can only be one of it too.
+    DuplicateValue {
+        line: usize,
+        column: usize,
+        value: usize,
+    },
+    /// A row has fewer values than the width of the board.
+    MissingValues {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A row has more values than the width of the board, column is the one of the first extra value.
+    ExtraValue {
+        line: usize,
+        column: usize,
+    },
+    /// The file has fewer rows than the height of the board.
+    MissingRows {
+        expected: usize,
+        found: usize,
+    },
+    /// The file has more rows than the height of the board.
+    ExtraRow {
+        line: usize,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -32,19 +101,48 @@ impl Direction {
             Direction::None => Direction::None,
         }
     }
-}
 
-#[derive(Debug)]
-pub enum Strategy {
-    Bfs([Direction; 4]),
-    Dfs([Direction; 4]),
-    AStar(Metric),
+    /// Returns the direction written as its first letter, like `U` for up.
+    pub fn from_letter(letter: char) -> Option<Direction> {
+        match letter {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None,
+        }
+    }
 }
 
+/// Order in which uninformed strategies expand neighbours.
 #[derive(Debug, Clone, Copy)]
-pub enum Metric {
-    Hamming,
-    Manhattan,
+pub enum Order {
+    Fixed([Direction; 4]),
+    /// Neighbours of every state are shuffled, using a generator with the given seed.
+    Random(u64),
+}
+
+/// How much the heuristic counts in the priority of A*.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Weight {
+    /// Priority is g + w·h. Weight of 1 finds the shortest solutions, larger ones find longer solutions faster.
+    Factor(f64),
+    /// Priority is only h, the path length is ignored (greedy best-first search).
+    Greedy,
+}
+
+impl Weight {
+    /// Returns priority of a state in A* from its path length and estimate, the state with the lowest one is processed first.
+    /// Weighted estimates too large for the priority are capped at its maximum.
+    pub(crate) fn priority(self, depth: usize, estimate: u32) -> u64 {
+        match self {
+            // Metric of a state is the sum of it's path length and given heuristic.
+            Weight::Factor(weight) => {
+                (depth as u64).saturating_add((weight * estimate as f64).round() as u64)
+            }
+            Weight::Greedy => estimate as u64,
+        }
+    }
 }
 
 /// Puzzle contains a single state of the game.
@@ -52,13 +150,118 @@ pub enum Metric {
 #[derive(Clone)]
 pub struct Puzzle {
     /// The cells of the puzzle.
-    // Right now we're using u8 for representing the cells, if width * height > 255, we'll need to change this.
-    grid: Vec<u8>,
+    grid: Grid,
     /// Series of moves that led to this state.
-    path: [Direction; MAX_DEPTH],
+    path: MoveLog,
     width: usize,
     height: usize,
-    metric: u32,
+}
+
+/// Limits of a single search.
+#[derive(Debug, Clone, Default)]
+pub struct SolveOptions {
+    /// Approximate number of bytes the stored states (visited ones and the queue) can take,
+    /// counting the room their tables have allocated for more.
+    pub memory_limit: Option<usize>,
+    /// Longest time the search can run for.
+    pub time_limit: Option<Duration>,
+    /// Most states the search can process.
+    pub processed_limit: Option<usize>,
+    /// Most states the search can keep as visited.
+    pub visited_limit: Option<usize>,
+}
+
+impl SolveOptions {
+    /// Returns the limit the search went over, if any.
+    /// Stored size is the approximate number of bytes taken by the visited states together with the queue.
+    pub(crate) fn limit_hit(
+        &self,
+        start_time: Instant,
+        processed_states: usize,
+        visited_states: usize,
+        stored_size: usize,
+    ) -> Option<Limit> {
+        if self
+            .processed_limit
+            .is_some_and(|limit| processed_states >= limit)
+        {
+            Some(Limit::ProcessedStates)
+        } else if self
+            .visited_limit
+            .is_some_and(|limit| visited_states > limit)
+        {
+            Some(Limit::VisitedStates)
+        } else if self.memory_limit.is_some_and(|limit| stored_size > limit) {
+            Some(Limit::Memory)
+        } else if self
+            .time_limit
+            .is_some_and(|limit| start_time.elapsed() > limit)
+        {
+            Some(Limit::Time)
+        } else {
+            None
+        }
+    }
+}
+
+/// Limit which cut a search short.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Memory,
+    Time,
+    ProcessedStates,
+    VisitedStates,
+}
+
+/// Series of moves packed two bits each, so that long paths stay cheap to store and clone.
+#[derive(Clone, Default)]
+struct MoveLog {
+    /// Four moves per byte, the first move in the lowest bits.
+    bits: Vec<u8>,
+    len: usize,
+}
+
+impl MoveLog {
+    fn push(&mut self, direction: Direction) {
+        let code = match direction {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+            Direction::None => panic!("Can't log a move without a direction!"),
+        };
+
+        if self.len.is_multiple_of(4) {
+            self.bits.push(0);
+        }
+        self.bits[self.len / 4] |= code << (self.len % 4 * 2);
+        self.len += 1;
+    }
+
+    fn get(&self, i: usize) -> Direction {
+        match (self.bits[i / 4] >> (i % 4 * 2)) & 0b11 {
+            0 => Direction::Up,
+            1 => Direction::Down,
+            2 => Direction::Left,
+            _ => Direction::Right,
+        }
+    }
+
+    fn last(&self) -> Option<Direction> {
+        if self.len == 0 {
+            None
+        } else {
+            Some(self.get(self.len - 1))
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn to_vec(&self) -> Vec<Direction> {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
 }
 
 /// Result of solving the puzzle.
@@ -73,6 +276,39 @@ pub struct SolveResult {
     pub max_depth: usize,
     /// Time spent in milliseconds.
     pub time_spent: u128,
+    /// Number of passes over the search tree (IDA* raises its threshold after each one).
+    pub iterations: usize,
+    /// Processed states of each pass, for strategies which make more than one.
+    pub iteration_states: Vec<usize>,
+    /// How the search ended, which tells why there is no path if there isn't one.
+    pub outcome: Outcome,
+    /// Node counts of each direction, for bidirectional searches.
+    pub bidirectional: Option<BidirectionalStats>,
+}
+
+/// How a search ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// The path leads to the goal.
+    Solved,
+    /// The parity check showed the goal can't be reached, so there was no search at all.
+    ProvenUnsolvableByParity,
+    /// The solver doesn't support boards of the puzzle's size, so there was no search at all.
+    UnsupportedBoard,
+    /// The search ran out of states without reaching the goal.
+    ProvenUnsolvable,
+    /// DFS searched every state within its depth limit without reaching the goal.
+    DepthLimitExhausted,
+    /// The search was stopped by one of the limits before it could finish.
+    ResourceLimitHit(Limit),
+}
+
+/// Node counts of a bidirectional search, split between its forward and backward halves.
+pub struct BidirectionalStats {
+    pub forward_visited_states: usize,
+    pub forward_processed_states: usize,
+    pub backward_visited_states: usize,
+    pub backward_processed_states: usize,
 }
 
 impl PartialEq for Puzzle {
@@ -89,40 +325,151 @@ impl Hash for Puzzle {
     }
 }
 
-impl Ord for Puzzle {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.metric.cmp(&other.metric).reverse()
+impl Puzzle {
+    /// Returns solved puzzle with the given dimensions.
+    pub fn solved(width: usize, height: usize) -> Result<Puzzle, BoardError> {
+        check_size(width, height)?;
+        Ok(Puzzle::goal(width, height))
     }
-}
 
-impl PartialOrd for Puzzle {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    /// Returns a puzzle with the given tiles in row-major order, 0 being the empty cell.
+    /// Tiles have to be numbered from 0 to `width * height - 1`, each appearing exactly once.
+    pub fn from_tiles(width: usize, height: usize, tiles: &[Tile]) -> Result<Puzzle, BoardError> {
+        let cells = check_size(width, height)?;
+        if tiles.len() != cells {
+            return Err(BoardError::WrongTileCount {
+                expected: cells,
+                found: tiles.len(),
+            });
+        }
+
+        // Whether every value has already appeared.
+        let mut seen = vec![false; cells];
+        for (index, &value) in tiles.iter().enumerate() {
+            if value as usize >= cells {
+                return Err(BoardError::ValueOutOfRange { index, value });
+            }
+            if seen[value as usize] {
+                return Err(BoardError::DuplicateValue { index, value });
+            }
+            seen[value as usize] = true;
+        }
+
+        Ok(Puzzle::from_cells(width, height, tiles.to_vec()))
     }
-}
 
-impl Puzzle {
-    /// Returns solved puzzle with the given dimensions.
-    pub fn _new(width: usize, height: usize) -> Puzzle {
+    /// Returns solved puzzle with the given dimensions, which have to be valid.
+    pub(crate) fn goal(width: usize, height: usize) -> Puzzle {
         let mut grid = vec![0; width * height];
         for y in 0..height {
             for x in 0..width {
-                grid[y * width + x] = (y * width + x + 1) as u8;
+                grid[y * width + x] = (y * width + x + 1) as Tile;
             }
         }
 
         grid[height * width - 1] = 0;
+        Puzzle {
+            grid: Grid::new(grid),
+            path: MoveLog::default(),
+            width,
+            height,
+        }
+    }
+
+    /// Returns a puzzle with the given cells, which have to be numbered from 0 to `width * height - 1`.
+    fn from_cells(width: usize, height: usize, cells: Vec<Tile>) -> Puzzle {
+        Puzzle::from_grid(width, height, Grid::new(cells))
+    }
+
+    /// Returns a puzzle with the given grid, which searches keep without the rest of the state.
+    pub(crate) fn from_grid(width: usize, height: usize, grid: Grid) -> Puzzle {
         Puzzle {
             grid,
-            path: [Direction::None; MAX_DEPTH],
+            path: MoveLog::default(),
             width,
             height,
-            metric: 0,
         }
     }
 
+    /// Returns a puzzle made by applying exactly `moves` random moves to the solved one, never undoing the previous move.
+    /// Board has to be at least 2x2, so that there's always a move which doesn't go back.
+    pub fn scrambled(
+        width: usize,
+        height: usize,
+        moves: usize,
+        rng: &mut Rng,
+    ) -> Result<Puzzle, BoardError> {
+        check_shuffle_size(width, height)?;
+        let mut puzzle = Puzzle::goal(width, height);
+        let mut last_move = Direction::None;
+
+        for _ in 0..moves {
+            let candidates: Vec<Direction> = INFORMED_ORDER
+                .into_iter()
+                .filter(|direction| direction.opposite() != last_move)
+                .filter(|direction| puzzle.move_empty(direction).is_some())
+                .collect();
+            last_move = candidates[rng.below(candidates.len())];
+            puzzle = puzzle.move_empty(&last_move).unwrap();
+        }
+
+        // Moves used for scrambling are not a part of the puzzle.
+        puzzle.path = MoveLog::default();
+        Ok(puzzle)
+    }
+
+    /// Returns a puzzle drawn uniformly from all solvable arrangements of the board.
+    /// Board has to be at least 2x2, on narrower ones the tiles can't pass each other.
+    pub fn random_solvable(
+        width: usize,
+        height: usize,
+        rng: &mut Rng,
+    ) -> Result<Puzzle, BoardError> {
+        check_shuffle_size(width, height)?;
+        let mut cells: Vec<Tile> = (0..width * height).map(|value| value as Tile).collect();
+        rng.shuffle(&mut cells);
+
+        let puzzle = Puzzle::from_cells(width, height, cells.clone());
+        if puzzle.is_solvable() {
+            return Ok(puzzle);
+        }
+
+        // Swapping two tiles flips the parity, and swapping the first two tiles pairs every unsolvable
+        // arrangement with exactly one solvable, so all the solvable ones stay equally likely.
+        let tiles: Vec<usize> = (0..cells.len())
+            .filter(|&i| cells[i] != 0)
+            .take(2)
+            .collect();
+        cells.swap(tiles[0], tiles[1]);
+        Ok(Puzzle::from_cells(width, height, cells))
+    }
+
+    /// Writes the puzzle to a file in the format read by `from_file`.
+    pub fn to_file(&self, path: &str) -> std::io::Result<()> {
+        let cell_width = (self.width * self.height - 1).to_string().len();
+        let mut contents = format!("{} {}\n", self.height, self.width);
+        for y in 0..self.height {
+            let row: Vec<String> = (0..self.width)
+                .map(|x| format!("{:>cell_width$}", self.grid.get(y * self.width + x)))
+                .collect();
+            contents.push_str(&row.join(" "));
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     /// Returns a puzzle from a file in which first line contains height and width
     /// and the next ones values of cells seperated by spaces.
+    /// Every row has to hold exactly `width` values and every number from 0 to `width * height - 1`
+    /// has to appear exactly once. Blank lines are skipped.
     pub fn from_file(path: &str) -> Result<Puzzle, FileReadError> {
         // Read contents of file, if we fail to do that, the file probably doesn't exist.
         let contents = fs::read_to_string(path).map_err(|_err| FileReadError::NotFound)?;
@@ -135,50 +482,112 @@ impl Puzzle {
 
         // If these two elements were valid, we pull them out of Option<Result<>>, otherwise the file is corrupted.
         let height = match dimensions.next() {
-            Some(Ok(height)) => height,
+            Some(Ok(height)) if height > 0 => height,
             _ => return Err(FileReadError::IsCorrupt),
         };
         let width = match dimensions.next() {
-            Some(Ok(width)) => width,
+            Some(Ok(width)) if width > 0 => width,
             _ => return Err(FileReadError::IsCorrupt),
         };
+        if dimensions.next().is_some() {
+            return Err(FileReadError::IsCorrupt);
+        }
+
+        // Every cell has to be numbered by a tile, including the empty one.
+        let cells = width
+            .checked_mul(height)
+            .filter(|&cells| cells <= Tile::MAX as usize + 1)
+            .ok_or(FileReadError::TooLarge)?;
 
         // Create a new grid of cells with the given dimensions.
-        let mut grid = vec![0; width * height];
+        let mut grid = Vec::with_capacity(cells);
+        // Whether every value has already appeared.
+        let mut seen = vec![false; cells];
 
         // Iterate over the lines of the file, starting from the second line.
-        for (y, line) in contents.lines().skip(1).enumerate() {
-            // Split the line by whitespace, and parse the elements from &str to u32.
-            let line_elements = line.split_whitespace().map(|s| s.parse::<u32>());
-            // Iterate over the elements of the line, and set the cell at the given coordinates to the value.
-            for (x, value) in line_elements.enumerate() {
-                let value = value.map_err(|_err| FileReadError::IsCorrupt)?;
-                grid[y * width + x] = value as u8;
+        for (i, line) in contents.lines().enumerate().skip(1) {
+            let line_number = i + 1;
+            // Split the line by whitespace, keeping the column every element starts at.
+            let line_elements = split_with_columns(line);
+            if line_elements.is_empty() {
+                continue;
+            }
+
+            if grid.len() == cells {
+                return Err(FileReadError::ExtraRow { line: line_number });
             }
+            if line_elements.len() < width {
+                return Err(FileReadError::MissingValues {
+                    line: line_number,
+                    expected: width,
+                    found: line_elements.len(),
+                });
+            }
+            if line_elements.len() > width {
+                return Err(FileReadError::ExtraValue {
+                    line: line_number,
+                    column: line_elements[width].0,
+                });
+            }
+
+            // Parse the elements from &str to usize, and push them to the grid.
+            for (column, element) in line_elements {
+                let value =
+                    element
+                        .parse::<usize>()
+                        .map_err(|_err| FileReadError::InvalidValue {
+                            line: line_number,
+                            column,
+                        })?;
+                // Values which don't fit would wrap around to other, valid ones.
+                if value >= cells {
+                    return Err(FileReadError::ValueOutOfRange {
+                        line: line_number,
+                        column,
+                        value,
+                    });
+                }
+                if seen[value] {
+                    return Err(FileReadError::DuplicateValue {
+                        line: line_number,
+                        column,
+                        value,
+                    });
+                }
+                seen[value] = true;
+                grid.push(value as Tile);
+            }
+        }
+
+        // Since there are no duplicates, having all the rows means every value appeared.
+        if grid.len() < cells {
+            return Err(FileReadError::MissingRows {
+                expected: height,
+                found: grid.len() / width,
+            });
         }
 
         Ok(Puzzle {
-            grid,
-            path: [Direction::None; MAX_DEPTH],
+            grid: Grid::new(grid),
+            path: MoveLog::default(),
             width,
             height,
-            metric: 0,
         })
     }
 
-    fn is_solved(&self) -> bool {
+    pub fn is_solved(&self) -> bool {
         let width = self.width;
         let height = self.height;
 
         // Check if 0 is on the last place.
-        if self.grid[self.grid.len() - 1] != 0 {
+        if self.grid.get(width * height - 1) != 0 {
             return false;
         }
 
         // Check if the numbers from all but last row are in order.
         for y in 0..(height - 1) {
             for x in 0..width {
-                if self.grid[y * width + x] != (y * width + x + 1) as u8 {
+                if self.grid.get(y * width + x) as usize != y * width + x + 1 {
                     return false;
                 }
             }
@@ -186,7 +595,7 @@ impl Puzzle {
 
         // Check last row (without the last number, which should be 0).
         for x in 0..(width - 1) {
-            if self.grid[width * (height - 1) + x] != ((height - 1) * width + x + 1) as u8 {
+            if self.grid.get(width * (height - 1) + x) as usize != (height - 1) * width + x + 1 {
                 return false;
             }
         }
@@ -195,10 +604,11 @@ impl Puzzle {
         true
     }
 
-    fn empty_position(&self) -> (usize, usize) {
+    /// Returns coordinates (y, x) of the empty cell.
+    pub fn empty_position(&self) -> (usize, usize) {
         for y in 0..self.height {
             for x in 0..self.width {
-                if self.grid[y * self.width + x] == 0 {
+                if self.grid.get(y * self.width + x) == 0 {
                     return (y, x);
                 }
             }
@@ -207,7 +617,19 @@ impl Puzzle {
         panic!("Puzzle is not solvable!");
     }
 
-    fn move_empty(&self, direction: &Direction) -> Option<Puzzle> {
+    /// Returns the state after moving the empty cell, or none if the move would leave the board.
+    pub fn move_empty(&self, direction: &Direction) -> Option<Puzzle> {
+        let mut new_puzzle = self.slide_empty(direction)?;
+
+        // Push the direction to the path which lead to this new state.
+        new_puzzle.path.push(*direction);
+
+        Some(new_puzzle)
+    }
+
+    /// Returns the state after moving the empty cell like `move_empty`, but without logging the move,
+    /// for searches which keep their paths elsewhere.
+    pub(crate) fn slide_empty(&self, direction: &Direction) -> Option<Puzzle> {
         let (y, x) = self.empty_position();
 
         let mut new_x = x;
@@ -247,22 +669,34 @@ impl Puzzle {
         let mut new_puzzle = self.clone();
 
         // Swap the empty cell with the cell in the given direction.
-        new_puzzle.grid[y * self.width + x] = new_puzzle.grid[new_y * self.width + new_x];
-        new_puzzle.grid[new_y * self.width + new_x] = 0;
-
-        // Push the direction to the path which lead to this new state.
-        for i in 0..new_puzzle.path.len() {
-            if new_puzzle.path[i] == Direction::None {
-                new_puzzle.path[i] = *direction;
-                break;
-            }
-        }
+        new_puzzle
+            .grid
+            .slide(new_y * self.width + new_x, y * self.width + x);
 
         Some(new_puzzle)
     }
 
+    /// Returns values of the cells in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = Tile> + '_ {
+        (0..self.width * self.height).map(|i| self.grid.get(i))
+    }
+
+    /// Returns value of the cell at the given coordinates, 0 being the empty cell.
+    pub fn tile(&self, x: usize, y: usize) -> Tile {
+        self.grid.get(y * self.width + x)
+    }
+
+    /// Returns indices of the cells every value is on, indexed by the values.
+    pub(crate) fn tile_positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.width * self.height];
+        for (i, value) in self.cells().enumerate() {
+            positions[value as usize] = i;
+        }
+        positions
+    }
+
     /// Returns correct coordinates of a given value.
-    pub fn correct_place(&self, value: u8) -> (usize, usize) {
+    pub fn correct_place(&self, value: Tile) -> (usize, usize) {
         if value == 0 {
             return (self.width - 1, self.height - 1);
         };
@@ -270,7 +704,7 @@ impl Puzzle {
         let x = (value - 1) as usize % self.width;
         let y = (value - 1) as usize / self.width;
 
-        return (x, y);
+        (x, y)
     }
 
     /// Returns a Manhattan metric score of a board.
@@ -278,291 +712,694 @@ impl Puzzle {
     /// from their correct position.
     pub fn manhattan_metric(&self) -> u32 {
         let mut score: u32 = 0;
-        for (i, value) in self.grid.iter().enumerate() {
+        for (i, value) in self.cells().enumerate() {
             // The empty cell (0) is not considered in Manhattan metric.
-            if *value == 0 {
+            if value == 0 {
                 continue;
             }
             // Check where value of the current cell is supposed to be.
-            let (correct_x, correct_y) = self.correct_place(*value);
+            let (correct_x, correct_y) = self.correct_place(value);
 
             let x = i % self.width;
             let y = i / self.width;
             // Add the difference between the current cell and its correct position to the score.
-            score += (x as i32 - correct_x as i32).abs() as u32;
-            score += (y as i32 - correct_y as i32).abs() as u32;
+            score += x.abs_diff(correct_x) as u32;
+            score += y.abs_diff(correct_y) as u32;
         }
         score
     }
 
+    /// Returns a Linear Conflict metric score of a board.
+    /// The score is the Manhattan metric plus two moves for every tile that has to leave its goal row
+    /// (or column) so that the tiles in it which are out of order can pass each other.
+    pub fn linear_conflict_metric(&self) -> u32 {
+        let mut conflicts = 0;
+
+        for y in 0..self.height {
+            // Goal columns of the tiles in this row that belong to it, from left to right.
+            let goals: Vec<usize> = (0..self.width)
+                .map(|x| self.grid.get(y * self.width + x))
+                .filter(|value| *value != 0)
+                .map(|value| self.correct_place(value))
+                .filter(|(_, correct_y)| *correct_y == y)
+                .map(|(correct_x, _)| correct_x)
+                .collect();
+            conflicts += goals.len() - longest_increasing_run(&goals);
+        }
+
+        for x in 0..self.width {
+            // Goal rows of the tiles in this column that belong to it, from top to bottom.
+            let goals: Vec<usize> = (0..self.height)
+                .map(|y| self.grid.get(y * self.width + x))
+                .filter(|value| *value != 0)
+                .map(|value| self.correct_place(value))
+                .filter(|(correct_x, _)| *correct_x == x)
+                .map(|(_, correct_y)| correct_y)
+                .collect();
+            conflicts += goals.len() - longest_increasing_run(&goals);
+        }
+
+        self.manhattan_metric() + 2 * conflicts as u32
+    }
+
+    /// Returns a Walking Distance metric score of a board.
+    /// The score is the number of vertical moves needed to bring every tile to its goal row
+    /// plus the number of horizontal moves needed to bring every tile to its goal column,
+    /// where tiles only have to swap with the empty cell, but not with each other.
+    pub fn walking_distance_metric(&self) -> u32 {
+        Tables::for_board(self.width, self.height).estimate(self.cells())
+    }
+
     /// Returns a Hamming metric score of a board.
     /// The score is the number of tiles that are on incorrect places.
     pub fn hamming_metric(&self) -> u32 {
         let mut score: u32 = 0;
-        for (i, value) in self.grid.iter().enumerate() {
+        for (i, value) in self.cells().enumerate() {
             // The empty cell (0) is not considered in Hamming metric.
-            if *value == 0 {
+            if value == 0 {
                 continue;
             }
             // Add 1 to the score if the current cell has the right value.
-            if *value != (i + 1) as u8 {
+            if value as usize != i + 1 {
                 score += 1;
             }
         }
         score
     }
 
-    /// Returns vector of all possible moves from the current state in the given order.
-    fn get_neighbour_states(&self, order: &[Direction; 4], metric: Option<Metric>) -> Vec<Puzzle> {
+    /// Returns vector of all possible moves from the current state in the given order,
+    /// or in a random one if a generator is given.
+    pub(crate) fn get_neighbour_states(
+        &self,
+        order: &[Direction; 4],
+        rng: Option<&mut Rng>,
+    ) -> Vec<Puzzle> {
+        let last_move = self.path.last().unwrap_or(Direction::None);
+        self.neighbours(last_move, order, rng)
+            .into_iter()
+            .map(|(mut neighbour, direction)| {
+                neighbour.path.push(direction);
+                neighbour
+            })
+            .collect()
+    }
+
+    /// Returns the states one move away together with their moves, like `get_neighbour_states`,
+    /// for searches which keep the last move of the state and its path elsewhere.
+    pub(crate) fn neighbours(
+        &self,
+        last_move: Direction,
+        order: &[Direction; 4],
+        rng: Option<&mut Rng>,
+    ) -> Vec<(Puzzle, Direction)> {
         let mut neighbours = Vec::new();
 
+        let mut order = *order;
+        if let Some(rng) = rng {
+            rng.shuffle(&mut order);
+        }
+
         for direction in order {
             // If were' going back to where we came from, skip it.
-            let last_move = if self.path_depth() > 0 {
-                self.path[self.path_depth() - 1]
-            } else {
-                Direction::None
-            };
             if direction.opposite() == last_move {
                 continue;
             }
 
-            if let Some(mut new_puzzle) = self.move_empty(direction) {
-                // For A* purposes
-                match &metric {
-                    Some(met) => match met {
-                        // Metric of a state is the sum of it's path length and given heuristic.
-                        Metric::Hamming => {
-                            new_puzzle.metric =
-                                new_puzzle.path_depth() as u32 + new_puzzle.hamming_metric();
-                        }
-                        Metric::Manhattan => {
-                            new_puzzle.metric =
-                                new_puzzle.path_depth() as u32 + new_puzzle.manhattan_metric();
-                        }
-                    },
-                    None => {}
-                }
-
-                neighbours.push(new_puzzle);
+            if let Some(new_puzzle) = self.slide_empty(&direction) {
+                neighbours.push((new_puzzle, direction));
             }
         }
         neighbours
     }
 
-    fn path_depth(&self) -> usize {
-        let mut depth = 0;
-        for i in 0..self.path.len() {
-            if self.path[i] != Direction::None {
-                depth += 1;
-            }
-        }
-        depth
+    pub(crate) fn path_depth(&self) -> usize {
+        self.path.len()
     }
 
-    fn path_to_vec(&self) -> Vec<Direction> {
-        let mut path = Vec::new();
-        for i in 0..self.path.len() {
-            if self.path[i] != Direction::None {
-                path.push(self.path[i]);
+    pub(crate) fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub(crate) fn path_to_vec(&self) -> Vec<Direction> {
+        self.path.to_vec()
+    }
+
+    /// Returns whether the puzzle can be brought to the solved state.
+    /// Every move keeps the parity of the number of inversions (pairs of tiles in the wrong order) when the width is odd.
+    /// When the width is even, vertical moves flip it, so we add the distance of the empty cell from the last row.
+    /// On boards a single cell wide tiles can't pass each other at all, so they have to be in order already.
+    pub fn is_solvable(&self) -> bool {
+        let tiles: Vec<Tile> = self.cells().filter(|&value| value != 0).collect();
+
+        if self.width < 2 || self.height < 2 {
+            return tiles.is_sorted();
+        }
+
+        let mut inversions = 0;
+        for i in 0..tiles.len() {
+            for j in (i + 1)..tiles.len() {
+                if tiles[i] > tiles[j] {
+                    inversions += 1;
+                }
             }
         }
-        path
-    }
 
-    pub fn solve(&self, strategy: &Strategy) -> SolveResult {
-        match strategy {
-            Strategy::Bfs(order) => self.solve_basic(order, false),
-            Strategy::Dfs(order) => self.solve_basic(order, true),
-            Strategy::AStar(metric) => self.solve_priority(metric),
+        if self.width.is_multiple_of(2) {
+            let (empty_y, _) = self.empty_position();
+            inversions += self.height - 1 - empty_y;
         }
+
+        inversions.is_multiple_of(2)
     }
 
-    fn solve_basic(&self, order: &[Direction; 4], is_dfs: bool) -> SolveResult {
-        // Queue of puzzles to be solved.
-        let mut queue = VecDeque::new();
-        // HashSet of already visited puzzles. We use it to check if we've already visited a puzzle.
-        let mut visited = HashSet::with_capacity(800000);
+    /// Solves the puzzle with the solver, stopping the search early if it hits one of the limits.
+    pub fn solve(&self, solver: &dyn Solver, options: &SolveOptions) -> SolveResult {
+        self.result_without_search(solver)
+            .unwrap_or_else(|| solver.solve(self, options))
+    }
 
-        // Push the initial state to the queue and visited.
-        queue.push_back(self.clone());
-        visited.insert(self.clone());
+    /// Solves the puzzle like `solve` does, reporting events of the search to the observer as they happen.
+    /// Only BFS, DFS and A* (including weighted and greedy search) report events, other solvers run unobserved.
+    pub fn solve_observed(
+        &self,
+        solver: &dyn Solver,
+        options: &SolveOptions,
+        observer: &mut dyn SearchObserver,
+    ) -> SolveResult {
+        self.result_without_search(solver)
+            .unwrap_or_else(|| solver.solve_observed(self, options, observer))
+    }
 
-        // Max depth of search tree.
-        let mut max_depth = 0;
+    /// Returns the result of a search which never started, if the solver doesn't support the board
+    /// or the puzzle is unsolvable. There's no point in searching if the parity check tells us
+    /// we'll never reach the goal.
+    pub(crate) fn result_without_search(&self, solver: &dyn Solver) -> Option<SolveResult> {
+        let start_time = Instant::now();
+        let outcome = if !solver.supports(self.width, self.height) {
+            Outcome::UnsupportedBoard
+        } else if !self.is_solvable() {
+            Outcome::ProvenUnsolvableByParity
+        } else {
+            return None;
+        };
 
-        let mut processed_states = 0;
+        Some(SolveResult {
+            path: None,
+            visited_states: 0,
+            processed_states: 0,
+            max_depth: 0,
+            time_spent: start_time.elapsed().as_nanos(),
+            iterations: 0,
+            iteration_states: Vec::new(),
+            outcome,
+            bidirectional: None,
+        })
+    }
 
-        // Start timer from now to either finding the solution or processing all possible states.
+    pub(crate) fn solve_bidirectional(
+        &self,
+        order: &[Direction; 4],
+        options: &SolveOptions,
+    ) -> SolveResult {
         let start_time = Instant::now();
 
-        // If we're doing DFS, we need to reverse the order of the moves.
-        let mut order = order.to_vec();
-        if is_dfs {
-            order.reverse();
-        }
-        let order: &[Direction; 4] = &[order[0], order[1], order[2], order[3]];
+        let mut forward = BfsSide::new(self);
+        let mut backward = BfsSide::new(&Puzzle::goal(self.width, self.height));
+
+        // State where the searches met.
+        let mut meeting = if self.is_solved() {
+            Some(self.clone())
+        } else {
+            None
+        };
 
-        // While the queue is not empty, we keep iterating.
-        while !queue.is_empty() {
-            let current_state: Puzzle;
-            // Depending on whetever we're doing BFS or DFS, we pop the first or last element.
-            if is_dfs {
-                current_state = queue.pop_back().unwrap();
+        let mut outcome = Outcome::ProvenUnsolvable;
+
+        // Expand a whole layer of the smaller frontier at a time.
+        // Once a layer meets the other search, the shortest of its meetings is the shortest solution.
+        while meeting.is_none() && !forward.frontier.is_empty() && !backward.frontier.is_empty() {
+            let expanded = if forward.frontier.len() <= backward.frontier.len() {
+                forward.expand_layer(order, &backward, options, start_time)
             } else {
-                current_state = queue.pop_front().unwrap();
+                backward.expand_layer(order, &forward, options, start_time)
+            };
+            match expanded {
+                Ok(layer_meeting) => meeting = layer_meeting,
+                Err(limit) => {
+                    outcome = Outcome::ResourceLimitHit(limit);
+                    break;
+                }
             }
+        }
 
-            // Insert current state into already visited states so that we don't visit it again.
-            // visited.insert(current_state.clone());
+        if meeting.is_some() {
+            outcome = Outcome::Solved;
+        }
 
-            processed_states += 1;
+        // Moves from the goal to the meeting state have to be undone in reverse order to get from there to the goal.
+        let path = meeting.map(|meeting| {
+            let mut path = trace_path(&forward.visited, &meeting);
+            path.extend(
+                trace_path(&backward.visited, &meeting)
+                    .iter()
+                    .rev()
+                    .map(|direction| direction.opposite()),
+            );
+            path
+        });
 
-            // Update the max depth of the search tree.
-            let depth = current_state.path_depth();
+        SolveResult {
+            path,
+            visited_states: forward.visited.len() + backward.visited.len(),
+            processed_states: forward.processed_states + backward.processed_states,
+            max_depth: forward.depth.max(backward.depth),
+            time_spent: start_time.elapsed().as_nanos(),
+            iterations: 1,
+            bidirectional: Some(BidirectionalStats {
+                forward_visited_states: forward.visited.len(),
+                forward_processed_states: forward.processed_states,
+                backward_visited_states: backward.visited.len(),
+                backward_processed_states: backward.processed_states,
+            }),
+            iteration_states: Vec::new(),
+            outcome,
+        }
+    }
 
-            if depth > max_depth {
-                max_depth = depth;
-            }
+    /// IDA* keeps no visited states, so only the time and processed states limits apply to it.
+    pub(crate) fn solve_ida(
+        &self,
+        heuristic: &dyn Heuristic,
+        options: &SolveOptions,
+    ) -> SolveResult {
+        // Moves leading from the initial state to the one currently searched.
+        // This is the only thing that grows with depth, so memory stays proportional to it.
+        let mut path = Vec::new();
+        let mut stats = IdaStats::default();
 
-            // If the current state is solved, we've found the solution.
-            if current_state.is_solved() {
-                return SolveResult {
-                    path: Some(current_state.path_to_vec()),
-                    max_depth,
-                    visited_states: visited.len(),
-                    processed_states,
-                    time_spent: start_time.elapsed().as_nanos(),
-                };
-            }
+        // First threshold is the heuristic of the initial state, since it never overestimates.
+        let estimate = heuristic.estimate(self);
+        let mut threshold = estimate;
+        let mut iterations = 0;
 
-            // For DFS skip generating neighbour states if we're at MAX_DEPTH depth.
-            if is_dfs && current_state.path[MAX_DEPTH - 1] != Direction::None {
-                continue;
+        let start_time = Instant::now();
+
+        let mut iteration_states = Vec::new();
+        let outcome = loop {
+            iterations += 1;
+            let processed_before = stats.processed_states;
+            let search = IdaSearchContext {
+                heuristic,
+                threshold,
+                options,
+                start_time,
+            };
+            let result = self.ida_search(&search, estimate, &mut path, &mut stats);
+            iteration_states.push(stats.processed_states - processed_before);
+            match result {
+                IdaSearch::Found => break Outcome::Solved,
+                // Next pass is allowed to go as far as the cheapest state that exceeded this threshold.
+                IdaSearch::Exceeded(next_threshold) => threshold = next_threshold,
+                IdaSearch::Exhausted => break Outcome::ProvenUnsolvable,
+                IdaSearch::Stopped(limit) => break Outcome::ResourceLimitHit(limit),
             }
+        };
 
-            // Get the neighbour states of the current state.
-            let neighbour_states = current_state.get_neighbour_states(order, None);
+        SolveResult {
+            path: if outcome == Outcome::Solved {
+                Some(path)
+            } else {
+                None
+            },
+            max_depth: stats.max_depth,
+            visited_states: stats.visited_states,
+            processed_states: stats.processed_states,
+            time_spent: start_time.elapsed().as_nanos(),
+            iterations,
+            iteration_states,
+            outcome,
+            bidirectional: None,
+        }
+    }
 
-            // Iterate over the neighbours.
-            for neighbour in neighbour_states {
-                // If the state has already been visited, we compare length of it's path with the current state's path.
-                if let Some(previous) = visited.get(&neighbour) {
-                    if previous.path_depth() > neighbour.path_depth() {
-                        // If neighbour's path to a certain state is shorter, we add it to the queue anyway,
-                        // because maybe this time it'll be able to reach the solution.
-                        queue.push_back(neighbour.clone());
-                        // We also replace the visited state.
-                        visited.replace(neighbour);
-                    }
-                } else {
-                    // If the neighbour is not visited, we push him to the queue and mark him as visited.
-                    queue.push_back(neighbour.clone());
-                    visited.insert(neighbour);
-                }
+    /// Runs DFS with increasing depth limits, so the first solution it finds is the shortest one.
+    /// States are only checked against the current path, so memory stays proportional to the depth.
+    pub(crate) fn solve_iddfs(&self, order: &Order, options: &SolveOptions) -> SolveResult {
+        let mut stats = IdaStats::default();
+        let mut iteration_states = Vec::new();
+
+        let start_time = Instant::now();
+
+        let (order, mut rng) = match order {
+            Order::Fixed(order) => (*order, None),
+            Order::Random(seed) => (INFORMED_ORDER, Some(Rng::new(*seed))),
+        };
+
+        let mut depth_limit = 0;
+        let (path, outcome) = loop {
+            let processed_before = stats.processed_states;
+            let search = IddfsSearchContext {
+                order,
+                depth_limit,
+                options,
+                start_time,
+            };
+            let mut ancestors = vec![self.grid.clone()];
+            let result = self.iddfs_search(&search, &mut ancestors, &mut stats, rng.as_mut());
+            iteration_states.push(stats.processed_states - processed_before);
+
+            match result {
+                DepthSearch::Found(goal) => break (Some(goal.path_to_vec()), Outcome::Solved),
+                DepthSearch::CutOff => depth_limit += 1,
+                // Nothing was too deep, so a deeper pass wouldn't find anything new either.
+                DepthSearch::Exhausted => break (None, Outcome::ProvenUnsolvable),
+                DepthSearch::Stopped(limit) => break (None, Outcome::ResourceLimitHit(limit)),
             }
-        }
+        };
 
-        // If we got here, the puzzle is unsolvable.
         SolveResult {
-            path: None,
-            max_depth,
-            visited_states: visited.len(),
-            processed_states,
+            path,
+            max_depth: stats.max_depth,
+            visited_states: stats.visited_states,
+            processed_states: stats.processed_states,
             time_spent: start_time.elapsed().as_nanos(),
+            iterations: iteration_states.len(),
+            iteration_states,
+            outcome,
+            bidirectional: None,
         }
     }
 
-    fn solve_priority(&self, metric: &Metric) -> SolveResult {
-        // Queue of puzzles to be solved.
-        let mut queue = BinaryHeap::new();
-        // Set of already visited states.
-        let mut visited = HashSet::with_capacity(800000);
+    /// Depth-first search which doesn't go deeper than the depth limit, or back to a state on the current path.
+    fn iddfs_search(
+        &self,
+        search: &IddfsSearchContext,
+        ancestors: &mut Vec<Grid>,
+        stats: &mut IdaStats,
+        mut rng: Option<&mut Rng>,
+    ) -> DepthSearch {
+        if let Some(limit) =
+            search
+                .options
+                .limit_hit(search.start_time, stats.processed_states, 0, 0)
+        {
+            return DepthSearch::Stopped(limit);
+        }
 
-        // Push the initial state to both queue and visited.
-        queue.push(self.clone());
-        visited.insert(self.clone());
+        stats.processed_states += 1;
+        let depth = self.path_depth();
+        if depth > stats.max_depth {
+            stats.max_depth = depth;
+        }
 
-        let mut max_depth = 0;
-        let mut processed_states = 0;
+        if self.is_solved() {
+            return DepthSearch::Found(self.clone());
+        }
 
-        let start_time = Instant::now();
+        if depth >= search.depth_limit {
+            return DepthSearch::CutOff;
+        }
+
+        let mut result = DepthSearch::Exhausted;
+        for neighbour in self.get_neighbour_states(&search.order, rng.as_deref_mut()) {
+            if ancestors.contains(&neighbour.grid) {
+                continue;
+            }
+            stats.visited_states += 1;
+
+            ancestors.push(neighbour.grid.clone());
+            match neighbour.iddfs_search(search, ancestors, stats, rng.as_deref_mut()) {
+                DepthSearch::Found(goal) => return DepthSearch::Found(goal),
+                DepthSearch::Stopped(limit) => return DepthSearch::Stopped(limit),
+                DepthSearch::CutOff => result = DepthSearch::CutOff,
+                DepthSearch::Exhausted => {}
+            }
+            ancestors.pop();
+        }
+
+        result
+    }
+
+    /// Depth-first search which doesn't go past states with f-cost (path length + heuristic) above the threshold.
+    fn ida_search(
+        &self,
+        search: &IdaSearchContext,
+        estimate: u32,
+        path: &mut Vec<Direction>,
+        stats: &mut IdaStats,
+    ) -> IdaSearch {
+        let cost = path.len() as u32 + estimate;
+        if cost > search.threshold {
+            return IdaSearch::Exceeded(cost);
+        }
+
+        if let Some(limit) =
+            search
+                .options
+                .limit_hit(search.start_time, stats.processed_states, 0, 0)
+        {
+            return IdaSearch::Stopped(limit);
+        }
 
-        // While the queue is not empty, continue iterating.
-        while !queue.is_empty() {
-            // Since we're using Priority Queue with a reversed order,
-            // we're popping the Puzzle with the smallest metric value.
-            let current_state = queue.pop().unwrap();
+        stats.processed_states += 1;
+        if path.len() > stats.max_depth {
+            stats.max_depth = path.len();
+        }
 
-            processed_states += 1;
+        if self.is_solved() {
+            return IdaSearch::Found;
+        }
 
-            let depth = current_state.path_depth();
+        let last_move = path.last().copied().unwrap_or(Direction::None);
+        let mut result = IdaSearch::Exhausted;
 
-            if depth > max_depth {
-                max_depth = depth;
+        for direction in &INFORMED_ORDER {
+            // Going back to where we came from can't lead anywhere new.
+            if direction.opposite() == last_move {
+                continue;
             }
 
-            if current_state.is_solved() {
-                let mut path = Vec::new();
-                for i in 0..current_state.path.len() {
-                    if current_state.path[i] != Direction::None {
-                        path.push(current_state.path[i]);
+            if let Some(neighbour) = self.move_empty(direction) {
+                stats.visited_states += 1;
+
+                let neighbour_estimate = search.heuristic.update(&neighbour, *direction, estimate);
+                path.push(*direction);
+                match neighbour.ida_search(search, neighbour_estimate, path, stats) {
+                    IdaSearch::Found => return IdaSearch::Found,
+                    IdaSearch::Stopped(limit) => return IdaSearch::Stopped(limit),
+                    IdaSearch::Exceeded(cost) => {
+                        result = match result {
+                            IdaSearch::Exceeded(lowest) if lowest <= cost => result,
+                            _ => IdaSearch::Exceeded(cost),
+                        };
                     }
+                    IdaSearch::Exhausted => {}
                 }
-                return SolveResult {
-                    // path: Some(current_state.path.to_vec()),
-                    path: Some(path),
-                    max_depth,
-                    visited_states: visited.len(),
-                    processed_states,
-                    time_spent: start_time.elapsed().as_nanos(),
-                };
+                path.pop();
             }
+        }
 
-            // We're creating any order array but since this is an A* algorithm it does not matter.
-            // We're doing it just so the get_neighbour_states works.
-            let order: &[Direction; 4] = &[
-                Direction::Left,
-                Direction::Up,
-                Direction::Right,
-                Direction::Down,
-            ];
+        result
+    }
+}
+
+/// Returns the number of cells of a board with the given dimensions, if it can have that many tiles.
+fn check_size(width: usize, height: usize) -> Result<usize, BoardError> {
+    width
+        .checked_mul(height)
+        .filter(|&cells| cells > 0 && cells <= Tile::MAX as usize + 1)
+        .ok_or(BoardError::InvalidSize)
+}
+
+/// Returns an error if a board with these dimensions can't be scrambled or shuffled.
+fn check_shuffle_size(width: usize, height: usize) -> Result<(), BoardError> {
+    check_size(width, height)?;
+    if width < 2 || height < 2 {
+        return Err(BoardError::TooSmall);
+    }
+    Ok(())
+}
+
+/// Splits a line by whitespace, returning every element together with the column (counted from 1) it starts at.
+fn split_with_columns(line: &str) -> Vec<(usize, &str)> {
+    let mut elements = Vec::new();
+    let mut start = None;
+    for (column, (i, character)) in line.char_indices().enumerate() {
+        match (character.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, i)),
+            (true, Some((start_column, start_index))) => {
+                elements.push((start_column, &line[start_index..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start_column, start_index)) = start {
+        elements.push((start_column, &line[start_index..]));
+    }
+    elements
+}
 
-            let neighbour_states = current_state.get_neighbour_states(order, Some(*metric));
-
-            for neighbour in neighbour_states {
-                // If the state has already been visited, we compare length of it's path with the current state's path.
-                if let Some(previous) = visited.get(&neighbour) {
-                    if previous.path_depth() > neighbour.path_depth() {
-                        // If neighbour's path to a certain state is shorter, we add it to the queue anyway,
-                        // because maybe this time it'll be able to reach the solution.
-                        queue.push(neighbour.clone());
-                        // We also replace the visited state.
-                        visited.replace(neighbour);
+/// Returns the length of the longest increasing subsequence of values.
+/// Tiles outside of it are the ones which have to step out of the line in linear conflict metric.
+/// Counting them instead of reversed pairs keeps the metric admissible when three or more tiles conflict.
+fn longest_increasing_run(values: &[usize]) -> usize {
+    let mut lengths = vec![1; values.len()];
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+            }
+        }
+    }
+    lengths.into_iter().max().unwrap_or(0)
+}
+
+/// One of the two searches run by bidirectional BFS.
+struct BfsSide {
+    width: usize,
+    height: usize,
+    /// States of the deepest layer, which are yet to be expanded.
+    frontier: Vec<Grid>,
+    visited: Links,
+    processed_states: usize,
+    /// Depth of the frontier.
+    depth: usize,
+}
+
+impl BfsSide {
+    fn new(start: &Puzzle) -> BfsSide {
+        let link = Link {
+            depth: 0,
+            last_move: Direction::None,
+        };
+        BfsSide {
+            width: start.width,
+            height: start.height,
+            frontier: vec![start.grid.clone()],
+            visited: Links::from([(start.grid.clone(), link)]),
+            processed_states: 0,
+            depth: 0,
+        }
+    }
+
+    /// Expands every state of the frontier, and returns the state with the shortest joined path,
+    /// if any of the new states was already reached by the other side.
+    /// Returns the limit instead, if the states kept by both sides went over it.
+    fn expand_layer(
+        &mut self,
+        order: &[Direction; 4],
+        other: &BfsSide,
+        options: &SolveOptions,
+        start_time: Instant,
+    ) -> Result<Option<Puzzle>, Limit> {
+        // Meeting state and the length of the joined path through it.
+        let mut meeting: Option<(Puzzle, usize)> = None;
+        let mut next_frontier = Vec::new();
+
+        let frontier = std::mem::take(&mut self.frontier);
+        for (i, grid) in frontier.iter().enumerate() {
+            let visited_states = self.visited.len() + other.visited.len();
+            let queued_states = (frontier.len() - i) + next_frontier.len() + other.frontier.len();
+            let queue_capacity =
+                frontier.capacity() + next_frontier.capacity() + other.frontier.capacity();
+            if let Some(limit) = options.limit_hit(
+                start_time,
+                self.processed_states + other.processed_states,
+                visited_states,
+                links_size(&self.visited, grid)
+                    + links_size(&other.visited, grid)
+                    + queue_size::<Grid>(queue_capacity, queued_states, grid),
+            ) {
+                return Err(limit);
+            }
+
+            self.processed_states += 1;
+
+            let state = Puzzle::from_grid(self.width, self.height, grid.clone());
+            let last_move = self.visited[grid].last_move;
+            for (neighbour, direction) in state.neighbours(last_move, order, None) {
+                if self.visited.contains_key(&neighbour.grid) {
+                    continue;
+                }
+
+                if let Some(reached) = other.visited.get(&neighbour.grid) {
+                    let length = self.depth + 1 + reached.depth as usize;
+                    if meeting.as_ref().is_none_or(|(_, best)| *best > length) {
+                        meeting = Some((neighbour.clone(), length));
                     }
-                } else {
-                    // If the neighbour is not visited, we push him to the queue and mark him as visited.
-                    queue.push(neighbour.clone());
-                    visited.insert(neighbour);
                 }
+
+                let link = Link {
+                    depth: self.depth as u32 + 1,
+                    last_move: direction,
+                };
+                self.visited.insert(neighbour.grid.clone(), link);
+                next_frontier.push(neighbour.grid);
             }
         }
 
-        SolveResult {
-            path: None,
-            max_depth,
-            visited_states: visited.len(),
-            processed_states,
-            time_spent: start_time.elapsed().as_nanos(),
-        }
+        self.frontier = next_frontier;
+        self.depth += 1;
+        Ok(meeting.map(|(state, _)| state))
     }
 }
 
+/// Outcome of a single IDA* pass.
+enum IdaSearch {
+    /// The goal was reached, the path leads to it.
+    Found,
+    /// The goal wasn't reached, holds the lowest f-cost that went over the threshold.
+    Exceeded(u32),
+    /// The goal wasn't reached and no state went over the threshold, so there is nothing left to search.
+    Exhausted,
+    /// The search hit one of the limits before it could finish.
+    Stopped(Limit),
+}
+
+/// Settings of a single IDA* pass, the same for all of its recursive calls.
+struct IdaSearchContext<'a> {
+    heuristic: &'a dyn Heuristic,
+    threshold: u32,
+    options: &'a SolveOptions,
+    start_time: Instant,
+}
+
+/// Outcome of a single IDDFS pass.
+enum DepthSearch {
+    /// The goal was reached, holds its state with the path leading to it.
+    Found(Puzzle),
+    /// The goal wasn't reached, but some states were left out for being too deep.
+    CutOff,
+    /// The goal wasn't reached and no state was too deep, so there is nothing left to search.
+    Exhausted,
+    /// The search hit one of the limits before it could finish.
+    Stopped(Limit),
+}
+
+/// Settings of a single IDDFS pass, the same for all of its recursive calls.
+struct IddfsSearchContext<'a> {
+    order: [Direction; 4],
+    depth_limit: usize,
+    options: &'a SolveOptions,
+    start_time: Instant,
+}
+
+/// Counters shared between recursive calls of IDA* and IDDFS.
+#[derive(Default)]
+struct IdaStats {
+    visited_states: usize,
+    processed_states: usize,
+    max_depth: usize,
+}
+
 impl std::fmt::Display for Puzzle {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Every cell is as wide as the largest value, but no narrower than 3 characters.
+        let cell_width = (self.width * self.height - 1).to_string().len().max(3);
         for y in 0..self.height {
             for x in 0..self.width {
-                write!(f, "{:3} ", self.grid[y * self.width + x])?;
+                write!(f, "{:>cell_width$} ", self.grid.get(y * self.width + x))?;
             }
             writeln!(f)?;
         }
@@ -576,6 +1413,27 @@ impl std::fmt::Display for SolveResult {
         writeln!(f, "Max depth: {}", self.max_depth)?;
         writeln!(f, "Visited states: {}", self.visited_states)?;
         writeln!(f, "Processed states: {}", self.processed_states)?;
+        writeln!(f, "Iterations: {}", self.iterations)?;
+        if !self.iteration_states.is_empty() {
+            writeln!(
+                f,
+                "Processed states of each iteration: {:?}",
+                self.iteration_states
+            )?;
+        }
+        writeln!(f, "Outcome: {}", self.outcome)?;
+        if let Some(stats) = &self.bidirectional {
+            writeln!(
+                f,
+                "Forward visited/processed states: {}/{}",
+                stats.forward_visited_states, stats.forward_processed_states
+            )?;
+            writeln!(
+                f,
+                "Backward visited/processed states: {}/{}",
+                stats.backward_visited_states, stats.backward_processed_states
+            )?;
+        }
         writeln!(
             f,
             "Time spent: {:.3}\n",
@@ -585,6 +1443,31 @@ impl std::fmt::Display for SolveResult {
     }
 }
 
+/// Written to output files and reports, so it has to stay stable.
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Solved => write!(f, "solved"),
+            Outcome::ProvenUnsolvableByParity => write!(f, "proven_unsolvable_by_parity"),
+            Outcome::UnsupportedBoard => write!(f, "unsupported_board"),
+            Outcome::ProvenUnsolvable => write!(f, "proven_unsolvable"),
+            Outcome::DepthLimitExhausted => write!(f, "depth_limit_exhausted"),
+            Outcome::ResourceLimitHit(limit) => write!(f, "resource_limit_hit:{}", limit),
+        }
+    }
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Limit::Memory => write!(f, "memory"),
+            Limit::Time => write!(f, "time"),
+            Limit::ProcessedStates => write!(f, "processed_states"),
+            Limit::VisitedStates => write!(f, "visited_states"),
+        }
+    }
+}
+
 impl std::fmt::Display for Direction {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -596,3 +1479,515 @@ impl std::fmt::Display for Direction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::{Hamming, LinearConflict, Manhattan};
+    use crate::pdb::{PatternDatabase, PdbError};
+    use crate::solver::{AStar, Bfs, BidirectionalBfs, IdaStar, Iddfs};
+    use crate::walking_distance::WalkingDistance;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[test]
+    fn a_star_finds_as_short_solutions_as_bfs() {
+        let mut rng = Rng::new(20);
+        let mut puzzles = Vec::new();
+        for _ in 0..4 {
+            puzzles.push(Puzzle::scrambled(3, 3, 30, &mut rng).unwrap());
+        }
+        for (width, height) in [(2, 3), (3, 2), (2, 4), (4, 2)] {
+            for _ in 0..3 {
+                puzzles.push(Puzzle::random_solvable(width, height, &mut rng).unwrap());
+            }
+        }
+
+        let options = SolveOptions::default();
+        for puzzle in &puzzles {
+            let optimal = puzzle
+                .solve(
+                    &Bfs {
+                        order: Order::Fixed(INFORMED_ORDER),
+                    },
+                    &options,
+                )
+                .path
+                .unwrap()
+                .len();
+
+            let heuristics: [Arc<dyn Heuristic>; 4] = [
+                Arc::new(Hamming),
+                Arc::new(Manhattan),
+                Arc::new(LinearConflict),
+                Arc::new(WalkingDistance),
+            ];
+            for heuristic in heuristics {
+                let solver = AStar {
+                    heuristic: heuristic.clone(),
+                    weight: Weight::Factor(1.0),
+                };
+                assert!(solver.finds_shortest_path());
+                let result = puzzle.solve(&solver, &options);
+                assert_eq!(
+                    result.path.map(|path| path.len()),
+                    Some(optimal),
+                    "{:?} on\n{}",
+                    heuristic,
+                    puzzle
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn solvers_are_not_run_on_boards_they_dont_support() {
+        let heuristic = Arc::new(PatternDatabase::build(3, 2, &[3, 2]).unwrap());
+        let puzzle = Puzzle::scrambled(3, 3, 20, &mut Rng::new(17)).unwrap();
+        let options = SolveOptions::default();
+
+        let a_star = AStar {
+            heuristic: heuristic.clone(),
+            weight: Weight::Factor(1.0),
+        };
+        let ida_star = IdaStar { heuristic };
+        for solver in [&a_star as &dyn Solver, &ida_star] {
+            let result = puzzle.solve(solver, &options);
+            assert_eq!(result.outcome, Outcome::UnsupportedBoard);
+            assert!(result.path.is_none());
+            assert_eq!(result.processed_states, 0);
+        }
+    }
+
+    #[test]
+    fn pattern_databases_tell_which_board_they_were_built_for() {
+        let a_star = AStar {
+            heuristic: Arc::new(PatternDatabase::build(3, 2, &[3, 2]).unwrap()),
+            weight: Weight::Factor(1.0),
+        };
+        let database = a_star
+            .heuristic()
+            .and_then(|heuristic| heuristic.pattern_database())
+            .unwrap();
+        assert!(database.check_size(3, 2).is_ok());
+        assert!(matches!(
+            database.check_size(4, 4),
+            Err(PdbError::SizeMismatch {
+                expected: (3, 2),
+                found: (4, 4)
+            })
+        ));
+
+        let manhattan = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+        assert!(manhattan.heuristic().unwrap().pattern_database().is_none());
+        assert!(Bfs {
+            order: Order::Fixed(INFORMED_ORDER)
+        }
+        .heuristic()
+        .is_none());
+    }
+
+    #[test]
+    fn large_weights_keep_estimates_in_order() {
+        let weight = Weight::Factor(1e10);
+        assert!(weight.priority(3, 2) < weight.priority(0, 3));
+        assert_eq!(Weight::Factor(f64::MAX).priority(5, 1), u64::MAX);
+
+        let puzzle = Puzzle::scrambled(3, 3, 30, &mut Rng::new(19)).unwrap();
+        let result = puzzle.solve(
+            &AStar {
+                heuristic: Arc::new(Manhattan),
+                weight,
+            },
+            &SolveOptions::default(),
+        );
+        assert!(leads_to_goal(&puzzle, &result.path.unwrap()));
+    }
+
+    #[test]
+    fn boards_narrower_than_two_cells_are_not_shuffled() {
+        let mut rng = Rng::new(21);
+        for (width, height) in [(1, 1), (1, 2), (2, 1), (1, 5), (4, 1)] {
+            assert!(matches!(
+                Puzzle::scrambled(width, height, 10, &mut rng),
+                Err(BoardError::TooSmall)
+            ));
+            assert!(matches!(
+                Puzzle::random_solvable(width, height, &mut rng),
+                Err(BoardError::TooSmall)
+            ));
+        }
+        assert!(matches!(
+            Puzzle::random_solvable(0, 3, &mut rng),
+            Err(BoardError::InvalidSize)
+        ));
+        assert!(Puzzle::random_solvable(2, 2, &mut rng)
+            .unwrap()
+            .is_solvable());
+    }
+
+    /// Returns all orderings of the tiles.
+    fn permutations(tiles: &[Tile]) -> Vec<Vec<Tile>> {
+        if tiles.is_empty() {
+            return vec![Vec::new()];
+        }
+        let mut all = Vec::new();
+        for (i, &first) in tiles.iter().enumerate() {
+            let mut rest = tiles.to_vec();
+            rest.remove(i);
+            for mut permutation in permutations(&rest) {
+                permutation.insert(0, first);
+                all.push(permutation);
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn parity_tells_which_boards_can_reach_the_goal() {
+        for (width, height) in [(2, 2), (2, 3), (3, 2), (4, 2), (1, 3), (3, 1), (1, 4)] {
+            // Walk every state reachable from the goal, moves can be undone so they're exactly the solvable ones.
+            let goal = Puzzle::goal(width, height);
+            let mut reachable = HashSet::from([goal.clone()]);
+            let mut queue = vec![goal];
+            while let Some(puzzle) = queue.pop() {
+                for direction in INFORMED_ORDER {
+                    if let Some(next) = puzzle.slide_empty(&direction) {
+                        if reachable.insert(next.clone()) {
+                            queue.push(next);
+                        }
+                    }
+                }
+            }
+
+            let tiles: Vec<Tile> = (0..(width * height) as Tile).collect();
+            let all = permutations(&tiles);
+            // Half of the arrangements can be reached, unless the tiles can't pass each other.
+            if width > 1 && height > 1 {
+                assert_eq!(reachable.len() * 2, all.len());
+            }
+            for tiles in all {
+                let puzzle = Puzzle::from_tiles(width, height, &tiles).unwrap();
+                assert_eq!(
+                    puzzle.is_solvable(),
+                    reachable.contains(&puzzle),
+                    "{}",
+                    puzzle
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unsolvable_puzzles_are_not_searched() {
+        // The goal with the last two tiles swapped.
+        let puzzle = Puzzle::from_tiles(
+            4,
+            4,
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15, 14, 0],
+        )
+        .unwrap();
+        assert!(!puzzle.is_solvable());
+
+        let result = puzzle.solve(
+            &Bfs {
+                order: Order::Fixed(INFORMED_ORDER),
+            },
+            &SolveOptions::default(),
+        );
+        assert_eq!(result.outcome, Outcome::ProvenUnsolvableByParity);
+        assert!(result.path.is_none());
+        assert_eq!(result.processed_states, 0);
+        assert_eq!(result.visited_states, 0);
+    }
+
+    #[test]
+    fn linear_conflict_adds_two_moves_per_tile_leaving_its_line() {
+        let metric = |tiles: &[Tile]| {
+            Puzzle::from_tiles(3, 3, tiles)
+                .unwrap()
+                .linear_conflict_metric()
+        };
+        assert_eq!(metric(&[1, 2, 3, 4, 5, 6, 7, 8, 0]), 0);
+        // 1 and 2 swapped in their row, one of them has to step out.
+        assert_eq!(metric(&[2, 1, 3, 4, 5, 6, 7, 8, 0]), 2 + 2);
+        // 1 and 4 swapped in their column.
+        assert_eq!(metric(&[4, 2, 3, 1, 5, 6, 7, 8, 0]), 2 + 2);
+        // A reversed row, two of its tiles have to step out.
+        assert_eq!(metric(&[3, 2, 1, 4, 5, 6, 7, 8, 0]), 4 + 4);
+        // Tiles in the right order, even if not in their cells, don't conflict.
+        assert_eq!(metric(&[0, 1, 2, 4, 5, 3, 7, 8, 6]), 2 + 2);
+        // Tiles which don't belong to the line don't conflict with the ones in it.
+        assert_eq!(metric(&[4, 1, 3, 2, 5, 6, 7, 8, 0]), 4);
+    }
+
+    #[test]
+    fn linear_conflict_lies_between_manhattan_and_the_solution_length() {
+        let mut rng = Rng::new(22);
+        let solver = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+        for _ in 0..10 {
+            let puzzle = Puzzle::random_solvable(3, 3, &mut rng).unwrap();
+            let optimal = puzzle
+                .solve(&solver, &SolveOptions::default())
+                .path
+                .unwrap();
+            let metric = puzzle.linear_conflict_metric();
+            assert!(puzzle.manhattan_metric() <= metric);
+            assert!(metric as usize <= optimal.len(), "{}", puzzle);
+        }
+    }
+
+    /// Returns whether making the moves on the puzzle solves it.
+    fn leads_to_goal(puzzle: &Puzzle, path: &[Direction]) -> bool {
+        let mut state = puzzle.clone();
+        for direction in path {
+            match state.move_empty(direction) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        state.is_solved()
+    }
+
+    #[test]
+    fn bidirectional_bfs_finds_as_short_solutions_as_bfs() {
+        let mut rng = Rng::new(7);
+        let options = SolveOptions::default();
+        let bfs = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+        let bidirectional = BidirectionalBfs {
+            order: INFORMED_ORDER,
+        };
+        for (width, height) in [(3, 3), (2, 4), (4, 2), (2, 3)] {
+            for _ in 0..3 {
+                let puzzle = Puzzle::random_solvable(width, height, &mut rng).unwrap();
+                let optimal = puzzle.solve(&bfs, &options).path.unwrap();
+
+                let result = puzzle.solve(&bidirectional, &options);
+                let path = result.path.unwrap();
+                assert_eq!(path.len(), optimal.len(), "{}", puzzle);
+                assert!(leads_to_goal(&puzzle, &path), "{:?} on\n{}", path, puzzle);
+            }
+        }
+    }
+
+    #[test]
+    fn bidirectional_bfs_counts_states_of_both_directions() {
+        let puzzle = Puzzle::random_solvable(3, 3, &mut Rng::new(8)).unwrap();
+        let bidirectional = BidirectionalBfs {
+            order: INFORMED_ORDER,
+        };
+
+        let result = puzzle.solve(&bidirectional, &SolveOptions::default());
+        let stats = result.bidirectional.unwrap();
+        assert!(stats.forward_processed_states > 0);
+        assert!(stats.backward_processed_states > 0);
+        assert_eq!(
+            stats.forward_processed_states + stats.backward_processed_states,
+            result.processed_states
+        );
+        assert_eq!(
+            stats.forward_visited_states + stats.backward_visited_states,
+            result.visited_states
+        );
+
+        // The searches meet halfway, so neither has to go deeper than the whole path.
+        let path_len = result.path.unwrap().len();
+        assert!(result.max_depth < path_len);
+
+        let solved = Puzzle::goal(3, 3).solve(&bidirectional, &SolveOptions::default());
+        assert_eq!(solved.path, Some(Vec::new()));
+        assert_eq!(solved.processed_states, 0);
+    }
+
+    #[test]
+    fn bidirectional_bfs_stops_at_the_limits() {
+        let puzzle = Puzzle::random_solvable(3, 3, &mut Rng::new(9)).unwrap();
+        let options = SolveOptions {
+            processed_limit: Some(100),
+            ..SolveOptions::default()
+        };
+
+        let result = puzzle.solve(
+            &BidirectionalBfs {
+                order: INFORMED_ORDER,
+            },
+            &options,
+        );
+        assert_eq!(
+            result.outcome,
+            Outcome::ResourceLimitHit(Limit::ProcessedStates)
+        );
+        assert!(result.path.is_none());
+    }
+
+    /// Writes the contents to a temporary file and reads a puzzle from it.
+    fn read_puzzle(name: &str, contents: &str) -> Result<Puzzle, FileReadError> {
+        let path = std::env::temp_dir().join(format!("fifteen_puzzle_{}.txt", name));
+        let path = path.to_str().unwrap();
+        fs::write(path, contents).unwrap();
+        let puzzle = Puzzle::from_file(path);
+        fs::remove_file(path).unwrap();
+        puzzle
+    }
+
+    #[test]
+    fn puzzle_files_are_read_when_valid() {
+        let puzzle = read_puzzle("valid", "2 3\n1 2 3\n\n4  0 5\n").unwrap();
+        assert_eq!((puzzle.width(), puzzle.height()), (3, 2));
+        assert_eq!(puzzle.cells().collect::<Vec<_>>(), [1, 2, 3, 4, 0, 5]);
+    }
+
+    #[test]
+    fn puzzle_file_errors_point_at_the_problem() {
+        assert!(matches!(
+            Puzzle::from_file("/nonexistent/puzzle.txt"),
+            Err(FileReadError::NotFound)
+        ));
+        assert!(matches!(
+            read_puzzle("empty", ""),
+            Err(FileReadError::IsEmpty)
+        ));
+        for header in ["3", "3 0", "3 x", "3 3 3"] {
+            assert!(matches!(
+                read_puzzle("header", &format!("{}\n1 2 3\n", header)),
+                Err(FileReadError::IsCorrupt)
+            ));
+        }
+        assert!(matches!(
+            read_puzzle("too_large", "300 300\n"),
+            Err(FileReadError::TooLarge)
+        ));
+        assert!(matches!(
+            read_puzzle("invalid", "2 2\n1 2\n3 -0\n"),
+            Err(FileReadError::InvalidValue { line: 3, column: 3 })
+        ));
+        assert!(matches!(
+            read_puzzle("out_of_range", "2 2\n1 4\n3 0\n"),
+            Err(FileReadError::ValueOutOfRange {
+                line: 2,
+                column: 3,
+                value: 4
+            })
+        ));
+        assert!(matches!(
+            read_puzzle("duplicate", "2 2\n1 2\n  2 0\n"),
+            Err(FileReadError::DuplicateValue {
+                line: 3,
+                column: 3,
+                value: 2
+            })
+        ));
+        // A second empty cell is a duplicate too.
+        assert!(matches!(
+            read_puzzle("two_empty", "2 2\n0 2\n0 1\n"),
+            Err(FileReadError::DuplicateValue {
+                line: 3,
+                column: 1,
+                value: 0
+            })
+        ));
+        assert!(matches!(
+            read_puzzle("missing_values", "2 2\n1 2\n3\n"),
+            Err(FileReadError::MissingValues {
+                line: 3,
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            read_puzzle("extra_value", "2 2\n1 2 3\n0 3\n"),
+            Err(FileReadError::ExtraValue { line: 2, column: 5 })
+        ));
+        assert!(matches!(
+            read_puzzle("missing_rows", "2 2\n1 2\n"),
+            Err(FileReadError::MissingRows {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            read_puzzle("extra_row", "2 2\n1 2\n3 0\n\n1 2\n"),
+            Err(FileReadError::ExtraRow { line: 5 })
+        ));
+    }
+
+    #[test]
+    fn iterative_deepening_finds_as_short_solutions_as_bfs() {
+        let mut rng = Rng::new(18);
+        let mut puzzles = Vec::new();
+        for _ in 0..3 {
+            puzzles.push(Puzzle::scrambled(3, 3, 14, &mut rng).unwrap());
+            puzzles.push(Puzzle::scrambled(2, 3, 12, &mut rng).unwrap());
+        }
+
+        let options = SolveOptions::default();
+        let bfs = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+        let iddfs = Iddfs {
+            order: Order::Random(3),
+        };
+        let ida_star = IdaStar {
+            heuristic: Arc::new(Manhattan),
+        };
+        for puzzle in &puzzles {
+            let optimal = puzzle.solve(&bfs, &options).path.unwrap().len();
+            for solver in [&iddfs as &dyn Solver, &ida_star] {
+                let path = puzzle.solve(solver, &options).path.unwrap();
+                assert_eq!(path.len(), optimal, "{:?} on\n{}", solver, puzzle);
+                assert!(leads_to_goal(puzzle, &path));
+            }
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_reports_states_of_every_pass() {
+        let puzzle = Puzzle::scrambled(3, 3, 14, &mut Rng::new(19)).unwrap();
+        let options = SolveOptions::default();
+
+        let result = puzzle.solve(
+            &Iddfs {
+                order: Order::Fixed(INFORMED_ORDER),
+            },
+            &options,
+        );
+        // One pass for every depth limit up to the length of the solution.
+        let path_len = result.path.unwrap().len();
+        assert_eq!(result.iterations, path_len + 1);
+        assert_eq!(result.iteration_states.len(), result.iterations);
+        assert_eq!(result.iteration_states[0], 1);
+        assert_eq!(
+            result.iteration_states.iter().sum::<usize>(),
+            result.processed_states
+        );
+
+        let result = puzzle.solve(
+            &IdaStar {
+                heuristic: Arc::new(Manhattan),
+            },
+            &options,
+        );
+        assert_eq!(result.iteration_states.len(), result.iterations);
+        assert_eq!(
+            result.iteration_states.iter().sum::<usize>(),
+            result.processed_states
+        );
+
+        let solved = Puzzle::goal(3, 3).solve(
+            &Iddfs {
+                order: Order::Fixed(INFORMED_ORDER),
+            },
+            &options,
+        );
+        assert_eq!(solved.path, Some(Vec::new()));
+        assert_eq!(solved.iteration_states, [1]);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::heuristic::{Hamming, Heuristic, LinearConflict, Manhattan};
+use crate::pdb::{PatternDatabase, PdbError};
+use crate::puzzle::{Direction, Order, Weight, DEFAULT_DFS_DEPTH};
+use crate::rng::Rng;
+use crate::solver::{AStar, Bfs, BidirectionalBfs, Dfs, IdaStar, Iddfs, Solver};
+use crate::walking_distance::WalkingDistance;
+use std::fmt;
+use std::sync::Arc;
+
+/// Pattern database file used by the `pdb` metric when no other one is given.
+pub const DEFAULT_PDB_FILE: &str = "pdb.bin";
+
+/// Parameter of the uninformed solvers.
+const ORDER: &str = "order of moves, like LUDR, or R for random";
+/// Parameter of the informed solvers.
+const METRIC: &str = "metric, one of manh, hamm, lcon, walk or pdb";
+
+#[derive(Debug)]
+pub enum RegistryError {
+    /// No solver is registered under the name.
+    UnknownSolver(String),
+    /// Another solver is already registered under the name.
+    DuplicateName(String),
+    /// The parameter isn't an order or metric the solver takes.
+    InvalidParameter(String),
+    /// The setting was given, but the solver doesn't use it, or doesn't with this parameter.
+    UnusedSetting(Setting),
+    PatternDatabase(String, PdbError),
+}
+
+/// Setting which tunes a solver, besides its parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    DepthLimit,
+    Seed,
+    Weight,
+    PdbFile,
+}
+
+/// Shown as the command line flag which gives the setting, without the dashes.
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Setting::DepthLimit => "depth",
+            Setting::Seed => "seed",
+            Setting::Weight => "weight",
+            Setting::PdbFile => "pdb",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Values of the settings, the ones which aren't given take their defaults.
+#[derive(Debug, Clone, Default)]
+pub struct SolverSettings {
+    /// How deep DFS goes, `DEFAULT_DFS_DEPTH` by default.
+    pub depth_limit: Option<usize>,
+    /// Seed of the random order, the current time by default.
+    pub seed: Option<u64>,
+    /// How much the heuristic counts in A*, f = g + w·h, 1 by default.
+    pub weight: Option<f64>,
+    /// File the `pdb` metric is loaded from, `DEFAULT_PDB_FILE` by default.
+    pub pdb_file: Option<String>,
+}
+
+impl SolverSettings {
+    /// Returns the settings which were given.
+    pub fn given(&self) -> Vec<Setting> {
+        let mut given = Vec::new();
+        if self.depth_limit.is_some() {
+            given.push(Setting::DepthLimit);
+        }
+        if self.seed.is_some() {
+            given.push(Setting::Seed);
+        }
+        if self.weight.is_some() {
+            given.push(Setting::Weight);
+        }
+        if self.pdb_file.is_some() {
+            given.push(Setting::PdbFile);
+        }
+        given
+    }
+
+    /// Returns only the given settings which are in the list, leaving out the others.
+    pub fn only(&self, settings: &[Setting]) -> SolverSettings {
+        SolverSettings {
+            depth_limit: self
+                .depth_limit
+                .filter(|_| settings.contains(&Setting::DepthLimit)),
+            seed: self.seed.filter(|_| settings.contains(&Setting::Seed)),
+            weight: self.weight.filter(|_| settings.contains(&Setting::Weight)),
+            pdb_file: self
+                .pdb_file
+                .clone()
+                .filter(|_| settings.contains(&Setting::PdbFile)),
+        }
+    }
+}
+
+/// Makes a solver from its parameter and the settings.
+pub type Create = fn(&str, &SolverSettings) -> Result<Arc<dyn Solver>, RegistryError>;
+
+/// Solver which can be made by its name, like the ones chosen on the command line.
+#[derive(Clone)]
+pub struct Registration {
+    /// Name the solver is chosen by, like `bfs`.
+    pub name: &'static str,
+    /// What the parameter given after the name means.
+    pub parameter: &'static str,
+    /// Settings which tune the solver, giving any other one is an error.
+    pub settings: &'static [Setting],
+    pub description: &'static str,
+    /// Returns `UnusedSetting` for a setting which the solver only uses with some parameters, if it's given with others.
+    pub create: Create,
+}
+
+/// Solvers which can be made by their names, listed in the order they were registered.
+#[derive(Clone, Default)]
+pub struct Registry {
+    registrations: Vec<Registration>,
+}
+
+impl Registry {
+    /// Returns a registry with no solvers.
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Returns a registry with every built-in solver.
+    pub fn builtin() -> Registry {
+        Registry {
+            registrations: BUILTIN.to_vec(),
+        }
+    }
+
+    /// Adds the solver, unless another one is already registered under its name.
+    pub fn register(&mut self, registration: Registration) -> Result<(), RegistryError> {
+        if self.find(registration.name).is_some() {
+            return Err(RegistryError::DuplicateName(registration.name.to_string()));
+        }
+        self.registrations.push(registration);
+        Ok(())
+    }
+
+    pub fn registrations(&self) -> &[Registration] {
+        &self.registrations
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Registration> {
+        self.registrations
+            .iter()
+            .find(|registration| registration.name == name)
+    }
+
+    /// Returns a solver from its name and parameter, like `bfs LUDR` or `astr manh`.
+    /// Settings which the solver doesn't use are an error, rather than being ignored.
+    pub fn create(
+        &self,
+        name: &str,
+        parameter: &str,
+        settings: &SolverSettings,
+    ) -> Result<Arc<dyn Solver>, RegistryError> {
+        let registration = self
+            .find(name)
+            .ok_or_else(|| RegistryError::UnknownSolver(name.to_string()))?;
+        if let Some(setting) = settings
+            .given()
+            .into_iter()
+            .find(|setting| !registration.settings.contains(setting))
+        {
+            return Err(RegistryError::UnusedSetting(setting));
+        }
+        (registration.create)(parameter, settings)
+    }
+}
+
+/// Every built-in solver, in the order they are listed.
+const BUILTIN: &[Registration] = &[
+    Registration {
+        name: "bfs",
+        parameter: ORDER,
+        settings: &[Setting::Seed],
+        description: "Breadth-first search",
+        create: |order, settings| {
+            Ok(Arc::new(Bfs {
+                order: parse_order(order, settings)?,
+            }))
+        },
+    },
+    Registration {
+        name: "dfs",
+        parameter: ORDER,
+        settings: &[Setting::DepthLimit, Setting::Seed],
+        description: "Depth-first search, down to the depth limit",
+        create: |order, settings| {
+            Ok(Arc::new(Dfs {
+                order: parse_order(order, settings)?,
+                depth_limit: settings.depth_limit.unwrap_or(DEFAULT_DFS_DEPTH),
+            }))
+        },
+    },
+    Registration {
+        name: "iddfs",
+        parameter: ORDER,
+        settings: &[Setting::Seed],
+        description: "Iterative-deepening depth-first search",
+        create: |order, settings| {
+            Ok(Arc::new(Iddfs {
+                order: parse_order(order, settings)?,
+            }))
+        },
+    },
+    Registration {
+        name: "bibfs",
+        parameter: "order of moves, like LUDR",
+        settings: &[],
+        description: "Bidirectional breadth-first search",
+        create: |order, _| {
+            Ok(Arc::new(BidirectionalBfs {
+                order: parse_directions(order)?,
+            }))
+        },
+    },
+    Registration {
+        name: "astr",
+        parameter: METRIC,
+        settings: &[Setting::Weight, Setting::PdbFile],
+        description: "A* search, weighted if the weight isn't 1",
+        create: |metric, settings| {
+            Ok(Arc::new(AStar {
+                heuristic: parse_heuristic(metric, settings)?,
+                weight: Weight::Factor(settings.weight.unwrap_or(1.0)),
+            }))
+        },
+    },
+    Registration {
+        name: "gbfs",
+        parameter: METRIC,
+        settings: &[Setting::PdbFile],
+        description: "Greedy best-first search",
+        create: |metric, settings| {
+            Ok(Arc::new(AStar {
+                heuristic: parse_heuristic(metric, settings)?,
+                weight: Weight::Greedy,
+            }))
+        },
+    },
+    Registration {
+        name: "idastr",
+        parameter: METRIC,
+        settings: &[Setting::PdbFile],
+        description: "Iterative-deepening A* search",
+        create: |metric, settings| {
+            Ok(Arc::new(IdaStar {
+                heuristic: parse_heuristic(metric, settings)?,
+            }))
+        },
+    },
+];
+
+/// Returns a fixed order of moves, or a random one seeded with the seed setting if the order is `R`.
+/// Fixed orders don't use the seed.
+fn parse_order(order: &str, settings: &SolverSettings) -> Result<Order, RegistryError> {
+    if order.eq_ignore_ascii_case("R") {
+        Ok(Order::Random(settings.seed.unwrap_or_else(Rng::time_seed)))
+    } else if settings.seed.is_some() {
+        Err(RegistryError::UnusedSetting(Setting::Seed))
+    } else {
+        Ok(Order::Fixed(parse_directions(order)?))
+    }
+}
+
+/// Returns the order of moves written as their first letters, like `LUDR`.
+fn parse_directions(order: &str) -> Result<[Direction; 4], RegistryError> {
+    let invalid = || RegistryError::InvalidParameter(order.to_string());
+    if order.chars().count() != 4 {
+        return Err(invalid());
+    }
+    let mut directions = [Direction::Up; 4];
+    for (i, letter) in order.to_uppercase().chars().enumerate() {
+        directions[i] = Direction::from_letter(letter).ok_or_else(invalid)?;
+    }
+    Ok(directions)
+}
+
+/// Returns the heuristic of the metric. Only the `pdb` metric uses the file setting.
+fn parse_heuristic(
+    metric: &str,
+    settings: &SolverSettings,
+) -> Result<Arc<dyn Heuristic>, RegistryError> {
+    if metric != "pdb" && settings.pdb_file.is_some() {
+        return Err(RegistryError::UnusedSetting(Setting::PdbFile));
+    }
+
+    let heuristic: Arc<dyn Heuristic> = match metric {
+        "manh" => Arc::new(Manhattan),
+        "hamm" => Arc::new(Hamming),
+        "lcon" => Arc::new(LinearConflict),
+        "walk" => Arc::new(WalkingDistance),
+        "pdb" => {
+            let file = settings.pdb_file.as_deref().unwrap_or(DEFAULT_PDB_FILE);
+            Arc::new(
+                PatternDatabase::load(file)
+                    .map_err(|err| RegistryError::PatternDatabase(file.to_string(), err))?,
+            )
+        }
+        _ => return Err(RegistryError::InvalidParameter(metric.to_string())),
+    };
+    Ok(heuristic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{Puzzle, SolveOptions};
+
+    /// Returns the setting the registry rejected, if any.
+    fn unused_setting(name: &str, parameter: &str, settings: &SolverSettings) -> Option<Setting> {
+        match Registry::builtin().create(name, parameter, settings) {
+            Err(RegistryError::UnusedSetting(setting)) => Some(setting),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn settings_which_dont_apply_are_rejected() {
+        let depth = SolverSettings {
+            depth_limit: Some(10),
+            ..SolverSettings::default()
+        };
+        assert_eq!(unused_setting("dfs", "LUDR", &depth), None);
+        assert_eq!(
+            unused_setting("bfs", "LUDR", &depth),
+            Some(Setting::DepthLimit)
+        );
+
+        let seed = SolverSettings {
+            seed: Some(3),
+            ..SolverSettings::default()
+        };
+        assert_eq!(unused_setting("iddfs", "R", &seed), None);
+        assert_eq!(unused_setting("iddfs", "LUDR", &seed), Some(Setting::Seed));
+
+        let weight = SolverSettings {
+            weight: Some(2.0),
+            ..SolverSettings::default()
+        };
+        assert_eq!(unused_setting("astr", "manh", &weight), None);
+        assert_eq!(
+            unused_setting("gbfs", "manh", &weight),
+            Some(Setting::Weight)
+        );
+        assert_eq!(
+            unused_setting("idastr", "manh", &weight),
+            Some(Setting::Weight)
+        );
+
+        let pdb_file = SolverSettings {
+            pdb_file: Some("pdb.bin".to_string()),
+            ..SolverSettings::default()
+        };
+        assert_eq!(
+            unused_setting("astr", "manh", &pdb_file),
+            Some(Setting::PdbFile)
+        );
+    }
+
+    #[test]
+    fn random_orders_with_the_same_seed_give_the_same_results() {
+        let puzzle = Puzzle::scrambled(3, 3, 12, &mut Rng::new(11)).unwrap();
+        let registry = Registry::builtin();
+        let solve = |name: &str, seed: u64| {
+            let settings = SolverSettings {
+                seed: Some(seed),
+                ..SolverSettings::default()
+            };
+            let solver = registry.create(name, "R", &settings).unwrap();
+            let result = puzzle.solve(solver.as_ref(), &SolveOptions::default());
+            (result.path, result.processed_states, result.visited_states)
+        };
+
+        for name in ["bfs", "dfs", "iddfs"] {
+            assert_eq!(solve(name, 5), solve(name, 5), "{}", name);
+        }
+        // Different seeds shuffle differently, which DFS is the most sensitive to.
+        assert_ne!(solve("dfs", 5), solve("dfs", 6));
+    }
+
+    #[test]
+    fn names_can_only_be_registered_once() {
+        let mut registry = Registry::builtin();
+        let bfs = registry.find("bfs").unwrap().clone();
+        assert!(matches!(
+            registry.register(bfs.clone()),
+            Err(RegistryError::DuplicateName(_))
+        ));
+
+        let mut empty = Registry::new();
+        assert!(empty.register(bfs).is_ok());
+        assert!(empty
+            .create("bfs", "LUDR", &SolverSettings::default())
+            .is_ok());
+        assert!(matches!(
+            empty.create("dfs", "LUDR", &SolverSettings::default()),
+            Err(RegistryError::UnknownSolver(_))
+        ));
+    }
+}
@@ -0,0 +1,790 @@
+use crate::grid::Grid;
+use crate::heuristic::Heuristic;
+use crate::links::{links_size, queue_size, trace_path, Link, Links};
+use crate::observer::{NoObserver, SearchObserver};
+use crate::puzzle::{
+    Direction, Order, Outcome, Puzzle, SolveOptions, SolveResult, Weight, INFORMED_ORDER,
+};
+use crate::rng::Rng;
+use crate::solver::{AStar, Bfs, Dfs, Solver};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// BFS, DFS or A* search which runs one expansion at a time, so that it can be paused, inspected and resumed.
+/// Running it to the end gives the same result as solving the puzzle with the same solver.
+pub struct SearchRun<O: SearchObserver = NoObserver> {
+    frontier: Frontier,
+    /// Dimensions of the board, which the states only keep the grids of.
+    width: usize,
+    height: usize,
+    options: SolveOptions,
+    /// Time spent stepping so far. Pauses between the calls don't count towards it,
+    /// nor towards the time limit.
+    active_time: Duration,
+    processed_states: usize,
+    max_depth: usize,
+    /// State expanded by the last step, which is the goal once it's found.
+    current_state: Option<Puzzle>,
+    /// Result of the search, once it's finished.
+    result: Option<SolveResult>,
+    observer: O,
+}
+
+/// States waiting to be processed, together with the states seen so far.
+enum Frontier {
+    Basic(BasicFrontier),
+    Priority(PriorityFrontier),
+}
+
+/// Queue of BFS, or stack of DFS if there is a depth limit.
+struct BasicFrontier {
+    queue: VecDeque<QueuedState>,
+    /// Already visited states, with the shortest known path to each.
+    visited: Links,
+    order: [Direction; 4],
+    /// Shuffles the order for every state, if the order is random.
+    rng: Option<Rng>,
+    depth_limit: Option<usize>,
+    /// Whether DFS left out any state because it was too deep, in which case running out of states doesn't prove anything.
+    cut_off: bool,
+}
+
+/// Open states of A*, with the lowest priority on top.
+struct PriorityFrontier {
+    queue: BinaryHeap<OpenState>,
+    /// Shortest known path (g being its length) of every state seen so far, open or already processed (closed).
+    best_links: Links,
+    heuristic: Arc<dyn Heuristic>,
+    weight: Weight,
+}
+
+/// State waiting in the queue of BFS or DFS.
+/// It keeps its own depth and last move, since DFS can queue a state again with a shorter path
+/// while the longer one is still waiting.
+struct QueuedState {
+    grid: Grid,
+    depth: u32,
+    last_move: Direction,
+}
+
+impl QueuedState {
+    /// Returns the initial state of the search.
+    fn start(puzzle: &Puzzle) -> QueuedState {
+        QueuedState {
+            grid: puzzle.grid().clone(),
+            depth: 0,
+            last_move: Direction::None,
+        }
+    }
+
+    fn link(&self) -> Link {
+        Link {
+            depth: self.depth,
+            last_move: self.last_move,
+        }
+    }
+}
+
+impl Frontier {
+    /// Returns the visited states with the shortest known path to each.
+    fn links(&self) -> &Links {
+        match self {
+            Frontier::Basic(frontier) => &frontier.visited,
+            Frontier::Priority(frontier) => &frontier.best_links,
+        }
+    }
+}
+
+impl BasicFrontier {
+    /// Returns approximate number of bytes taken by the visited states and the queue,
+    /// whose grids are as large as the sample.
+    fn memory_size(&self, sample: &Grid) -> usize {
+        links_size(&self.visited, sample)
+            + queue_size::<QueuedState>(self.queue.capacity(), self.queue.len(), sample)
+    }
+}
+
+impl PriorityFrontier {
+    /// Returns approximate number of bytes taken by the seen states and the queue,
+    /// whose grids are as large as the sample.
+    fn memory_size(&self, sample: &Grid) -> usize {
+        links_size(&self.best_links, sample)
+            + queue_size::<OpenState>(self.queue.capacity(), self.queue.len(), sample)
+    }
+}
+
+/// State waiting in the A* queue.
+struct OpenState {
+    priority: u64,
+    /// Among equal priorities, the state with the greater tie breaker is processed first.
+    tie_breaker: usize,
+    /// Heuristic estimate of the state, so that its neighbours can update it instead of starting over.
+    estimate: u32,
+    state: QueuedState,
+}
+
+/// Orders states so that the binary heap, which pops the greatest one, pops the lowest priority first.
+impl Ord for OpenState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| self.tie_breaker.cmp(&other.tie_breaker))
+    }
+}
+
+impl PartialOrd for OpenState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Equal when the order doesn't tell them apart, to agree with `Ord`.
+impl PartialEq for OpenState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenState {}
+
+/// Returns the tie breaker of a state in A* from its path length.
+/// In A* ties go to deeper states, which are closer to the goal since their heuristic is lower.
+/// Greedy search ignores the path length, so there they go to shallower states to keep the solution short.
+fn tie_breaker(weight: Weight, depth: usize) -> usize {
+    match weight {
+        Weight::Factor(_) => depth,
+        Weight::Greedy => usize::MAX - depth,
+    }
+}
+
+/// Solver whose search can be run one expansion at a time, which BFS, DFS and A* are.
+pub trait Stepwise: Solver {
+    /// Returns the search of the puzzle before its first step.
+    /// It doesn't check the puzzle first, `SearchRun::new` and `SearchRun::with_observer` do.
+    fn start<O: SearchObserver>(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O>;
+}
+
+impl Stepwise for Bfs {
+    fn start<O: SearchObserver>(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        SearchRun::basic(puzzle, &self.order, None, options, observer)
+    }
+}
+
+impl Stepwise for Dfs {
+    fn start<O: SearchObserver>(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        SearchRun::basic(
+            puzzle,
+            &self.order,
+            Some(self.depth_limit),
+            options,
+            observer,
+        )
+    }
+}
+
+impl Stepwise for AStar {
+    fn start<O: SearchObserver>(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        SearchRun::priority(
+            puzzle,
+            self.heuristic.clone(),
+            self.weight,
+            options,
+            observer,
+        )
+    }
+}
+
+impl SearchRun {
+    /// Starts solving the puzzle with the solver.
+    /// An unsolvable puzzle, or a board the solver doesn't support, gives a run which has already finished,
+    /// like solving it does.
+    pub fn new<S: Stepwise>(puzzle: &Puzzle, solver: &S, options: &SolveOptions) -> SearchRun {
+        SearchRun::with_observer(puzzle, solver, options, NoObserver)
+    }
+}
+
+impl<O: SearchObserver> SearchRun<O> {
+    /// Starts solving the puzzle like `new` does, reporting events of the search to the observer.
+    pub fn with_observer<S: Stepwise>(
+        puzzle: &Puzzle,
+        solver: &S,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        // Starting the search could already estimate a board the heuristic doesn't support.
+        match puzzle.result_without_search(solver) {
+            Some(result) => SearchRun::finished(puzzle, result, options, observer),
+            None => solver.start(puzzle, options, observer),
+        }
+    }
+
+    /// Returns a run which ended with the result before searching anything.
+    fn finished(
+        puzzle: &Puzzle,
+        result: SolveResult,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        let frontier = Frontier::Basic(BasicFrontier {
+            queue: VecDeque::new(),
+            visited: HashMap::new(),
+            order: INFORMED_ORDER,
+            rng: None,
+            depth_limit: None,
+            cut_off: false,
+        });
+        let mut run = SearchRun::start(frontier, puzzle, options, observer);
+        run.result = Some(result);
+        run
+    }
+
+    /// Starts BFS, or DFS if a depth limit is given.
+    fn basic(
+        puzzle: &Puzzle,
+        order: &Order,
+        depth_limit: Option<usize>,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        let mut queue = VecDeque::new();
+        let mut visited = HashMap::new();
+
+        // Push the initial state to the queue and visited.
+        queue.push_back(QueuedState::start(puzzle));
+        visited.insert(puzzle.grid().clone(), QueuedState::start(puzzle).link());
+
+        // For random order we start from any order, since it gets shuffled for every state anyway.
+        let (mut order, rng) = match order {
+            Order::Fixed(order) => (*order, None),
+            Order::Random(seed) => (INFORMED_ORDER, Some(Rng::new(*seed))),
+        };
+
+        // If we're doing DFS, we need to reverse the order of the moves.
+        if depth_limit.is_some() {
+            order.reverse();
+        }
+
+        let frontier = Frontier::Basic(BasicFrontier {
+            queue,
+            visited,
+            order,
+            rng,
+            depth_limit,
+            cut_off: false,
+        });
+        SearchRun::start(frontier, puzzle, options, observer)
+    }
+
+    /// Starts A*, which only finds the shortest solution if the weight is 1 and the heuristic is admissible.
+    fn priority(
+        puzzle: &Puzzle,
+        heuristic: Arc<dyn Heuristic>,
+        weight: Weight,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        let mut queue = BinaryHeap::new();
+        let mut best_links = HashMap::new();
+
+        let estimate = heuristic.estimate(puzzle);
+        queue.push(OpenState {
+            priority: weight.priority(0, estimate),
+            tie_breaker: tie_breaker(weight, 0),
+            estimate,
+            state: QueuedState::start(puzzle),
+        });
+        best_links.insert(puzzle.grid().clone(), QueuedState::start(puzzle).link());
+
+        let frontier = Frontier::Priority(PriorityFrontier {
+            queue,
+            best_links,
+            heuristic,
+            weight,
+        });
+        SearchRun::start(frontier, puzzle, options, observer)
+    }
+
+    fn start(
+        frontier: Frontier,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: O,
+    ) -> SearchRun<O> {
+        SearchRun {
+            frontier,
+            width: puzzle.width(),
+            height: puzzle.height(),
+            options: options.clone(),
+            active_time: Duration::ZERO,
+            processed_states: 0,
+            max_depth: 0,
+            current_state: None,
+            result: None,
+            observer,
+        }
+    }
+
+    /// Processes the next state of the frontier. Returns whether the search is still running afterwards.
+    pub fn step(&mut self) -> bool {
+        self.run_until(self.processed_states + 1)
+    }
+
+    /// Steps until the search has processed the given number of states in total, or has finished.
+    /// Returns whether the search is still running afterwards.
+    pub fn run_until(&mut self, processed_states: usize) -> bool {
+        // The search is timed as if it started this much earlier and was never paused.
+        let resumed = Instant::now();
+        let start_time = resumed - self.active_time;
+        while self.result.is_none() && self.processed_states < processed_states {
+            self.advance(start_time);
+        }
+        self.active_time += resumed.elapsed();
+        self.result.is_none()
+    }
+
+    /// Runs the search to the end and returns its result.
+    pub fn finish(mut self) -> SolveResult {
+        self.run_until(usize::MAX);
+        self.result.unwrap()
+    }
+
+    /// Processes the next state, and stores the result if the search ended.
+    fn advance(&mut self, start_time: Instant) {
+        let outcome = match self.frontier {
+            Frontier::Basic(_) => self.step_basic(start_time),
+            Frontier::Priority(_) => self.step_priority(start_time),
+        };
+
+        if let Some(outcome) = outcome {
+            let path = match outcome {
+                Outcome::Solved => self
+                    .current_state
+                    .as_ref()
+                    .map(|goal| trace_path(self.frontier.links(), goal)),
+                _ => None,
+            };
+            self.result = Some(SolveResult {
+                path,
+                max_depth: self.max_depth,
+                visited_states: self.visited_states(),
+                processed_states: self.processed_states,
+                time_spent: start_time.elapsed().as_nanos(),
+                iterations: 1,
+                iteration_states: Vec::new(),
+                outcome,
+                bidirectional: None,
+            });
+        }
+    }
+
+    /// Returns the result of the search, once it has finished.
+    pub fn result(&self) -> Option<&SolveResult> {
+        self.result.as_ref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Returns the state expanded by the last step, or the goal once it's found.
+    pub fn current_state(&self) -> Option<&Puzzle> {
+        self.current_state.as_ref()
+    }
+
+    /// Returns the number of states waiting to be processed.
+    pub fn frontier_size(&self) -> usize {
+        match &self.frontier {
+            Frontier::Basic(frontier) => frontier.queue.len(),
+            Frontier::Priority(frontier) => frontier.queue.len(),
+        }
+    }
+
+    pub fn visited_states(&self) -> usize {
+        self.frontier.links().len()
+    }
+
+    pub fn processed_states(&self) -> usize {
+        self.processed_states
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns the time spent stepping so far, leaving out the pauses between the calls.
+    pub fn active_time(&self) -> Duration {
+        self.active_time
+    }
+
+    /// Processes the next state of BFS or DFS, and returns the outcome if the search ended.
+    fn step_basic(&mut self, start_time: Instant) -> Option<Outcome> {
+        let Frontier::Basic(frontier) = &mut self.frontier else {
+            unreachable!()
+        };
+
+        // Depending on whetever we're doing BFS or DFS, we pop the first or last element.
+        let next_state = if frontier.depth_limit.is_some() {
+            frontier.queue.pop_back()
+        } else {
+            frontier.queue.pop_front()
+        };
+
+        // If we ran out of states, the puzzle is unsolvable, or the solution is deeper than DFS can go.
+        let Some(queued) = next_state else {
+            return Some(if frontier.cut_off {
+                Outcome::DepthLimitExhausted
+            } else {
+                Outcome::ProvenUnsolvable
+            });
+        };
+
+        // Stop if the search went on for longer, or kept more, than we're allowed to.
+        if let Some(limit) = self.options.limit_hit(
+            start_time,
+            self.processed_states,
+            frontier.visited.len(),
+            frontier.memory_size(&queued.grid),
+        ) {
+            return Some(Outcome::ResourceLimitHit(limit));
+        }
+
+        self.processed_states += 1;
+
+        let depth = queued.depth as usize;
+        if depth > self.max_depth {
+            self.max_depth = depth;
+            self.observer.depth_increased(depth);
+        }
+
+        let current_state = Puzzle::from_grid(self.width, self.height, queued.grid);
+
+        // If the current state is solved, we've found the solution.
+        if current_state.is_solved() {
+            self.observer.goal_found(&current_state, depth);
+            self.current_state = Some(current_state);
+            return Some(Outcome::Solved);
+        }
+
+        // For DFS skip generating neighbour states if we're at the depth limit.
+        if frontier.depth_limit.is_some_and(|limit| depth >= limit) {
+            frontier.cut_off = true;
+            self.current_state = Some(current_state);
+            return None;
+        }
+
+        self.observer.expanded(&current_state, depth);
+
+        let neighbours =
+            current_state.neighbours(queued.last_move, &frontier.order, frontier.rng.as_mut());
+        for (neighbour, direction) in neighbours {
+            self.observer.generated(&neighbour, depth + 1);
+
+            let link = Link {
+                depth: queued.depth + 1,
+                last_move: direction,
+            };
+            // If the state has already been visited, we compare length of it's path with the current state's path.
+            match frontier.visited.entry(neighbour.grid().clone()) {
+                Entry::Occupied(mut previous) => {
+                    if previous.get().depth <= link.depth {
+                        self.observer.duplicate_pruned(&neighbour, depth + 1);
+                        continue;
+                    }
+                    // If neighbour's path to a certain state is shorter, we add it to the queue anyway,
+                    // because maybe this time it'll be able to reach the solution.
+                    // We also replace the visited state's path.
+                    previous.insert(link);
+                }
+                // If the neighbour is not visited, we push him to the queue and mark him as visited.
+                Entry::Vacant(entry) => {
+                    entry.insert(link);
+                }
+            }
+            frontier.queue.push_back(QueuedState {
+                grid: neighbour.grid().clone(),
+                depth: link.depth,
+                last_move: direction,
+            });
+        }
+
+        self.current_state = Some(current_state);
+        None
+    }
+
+    /// Processes the next state of A*, and returns the outcome if the search ended.
+    fn step_priority(&mut self, start_time: Instant) -> Option<Outcome> {
+        let Frontier::Priority(frontier) = &mut self.frontier else {
+            unreachable!()
+        };
+
+        let (queued, current_estimate) = loop {
+            let Some(open) = frontier.queue.pop() else {
+                return Some(Outcome::ProvenUnsolvable);
+            };
+
+            // A shorter path to this state was found after this one was queued, so this one is outdated.
+            if frontier.best_links[&open.state.grid].depth >= open.state.depth {
+                break (open.state, open.estimate);
+            }
+        };
+
+        if let Some(limit) = self.options.limit_hit(
+            start_time,
+            self.processed_states,
+            frontier.best_links.len(),
+            frontier.memory_size(&queued.grid),
+        ) {
+            return Some(Outcome::ResourceLimitHit(limit));
+        }
+
+        self.processed_states += 1;
+
+        let depth = queued.depth as usize;
+        if depth > self.max_depth {
+            self.max_depth = depth;
+            self.observer.depth_increased(depth);
+        }
+
+        let current_state = Puzzle::from_grid(self.width, self.height, queued.grid);
+
+        // Checking the goal only once it's taken from the queue (and not when it's generated)
+        // guarantees there's no shorter path left in the queue.
+        if current_state.is_solved() {
+            self.observer.goal_found(&current_state, depth);
+            self.current_state = Some(current_state);
+            return Some(Outcome::Solved);
+        }
+
+        self.observer.expanded(&current_state, depth);
+
+        for (neighbour, direction) in
+            current_state.neighbours(queued.last_move, &INFORMED_ORDER, None)
+        {
+            self.observer.generated(&neighbour, depth + 1);
+
+            let link = Link {
+                depth: queued.depth + 1,
+                last_move: direction,
+            };
+            // A state is only queued again if its path is shorter than any found before. It can be
+            // reopened even if it was already processed, which inconsistent heuristics need to stay optimal.
+            match frontier.best_links.entry(neighbour.grid().clone()) {
+                Entry::Occupied(mut best) => {
+                    if best.get().depth <= link.depth {
+                        self.observer.duplicate_pruned(&neighbour, depth + 1);
+                        continue;
+                    }
+                    best.insert(link);
+                }
+                Entry::Vacant(best) => {
+                    best.insert(link);
+                }
+            }
+
+            let estimate = frontier
+                .heuristic
+                .update(&neighbour, direction, current_estimate);
+            frontier.queue.push(OpenState {
+                priority: frontier.weight.priority(depth + 1, estimate),
+                tie_breaker: tie_breaker(frontier.weight, depth + 1),
+                estimate,
+                state: QueuedState {
+                    grid: neighbour.grid().clone(),
+                    depth: link.depth,
+                    last_move: direction,
+                },
+            });
+        }
+
+        self.current_state = Some(current_state);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::Manhattan;
+    use crate::pdb::PatternDatabase;
+
+    /// Path length, processed and visited states, and maximum depth a search ends with.
+    type Totals = (Option<usize>, usize, usize, usize);
+
+    /// Boards the searches below run on: three 3x3 boards and a scrambled 4x4 one.
+    fn boards() -> Vec<Puzzle> {
+        let mut rng = Rng::new(25);
+        let mut boards: Vec<_> = (0..3)
+            .map(|_| Puzzle::random_solvable(3, 3, &mut rng).unwrap())
+            .collect();
+        boards.push(Puzzle::scrambled(4, 4, 30, &mut rng).unwrap());
+        boards
+    }
+
+    fn totals(result: &SolveResult) -> Totals {
+        (
+            result.path.as_ref().map(|path| path.len()),
+            result.processed_states,
+            result.visited_states,
+            result.max_depth,
+        )
+    }
+
+    /// Steps through the search, pausing after the first few states, and checks the totals it ends with.
+    fn check_stepping<S: Stepwise>(puzzle: &Puzzle, solver: &S, expected: Totals) {
+        let mut run = SearchRun::new(puzzle, solver, &SolveOptions::default());
+        assert!(run.run_until(10));
+        assert_eq!(run.processed_states(), 10);
+        while run.step() {
+            assert!(run.current_state().is_some());
+        }
+
+        let result = run.result().unwrap();
+        assert_eq!(totals(result), expected, "{:?} on\n{}", solver, puzzle);
+        if result.path.is_some() {
+            assert_eq!(result.outcome, Outcome::Solved);
+        }
+    }
+
+    // The totals were recorded from the searches before they could be run in steps.
+    #[test]
+    fn stepped_searches_end_with_the_recorded_totals() {
+        let boards = boards();
+        let bfs = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+        let random_dfs = Dfs {
+            order: Order::Random(7),
+            depth_limit: 12,
+        };
+        let dfs = Dfs {
+            order: Order::Fixed(INFORMED_ORDER),
+            depth_limit: 40,
+        };
+        let a_star = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+        let greedy = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Greedy,
+        };
+
+        check_stepping(&boards[0], &bfs, (Some(22), 103863, 124382, 22));
+        check_stepping(&boards[0], &random_dfs, (None, 2717, 2389, 12));
+        check_stepping(&boards[0], &dfs, (Some(40), 52214, 38703, 40));
+        check_stepping(&boards[0], &a_star, (Some(22), 217, 351, 22));
+        check_stepping(&boards[0], &greedy, (Some(68), 390, 654, 80));
+
+        check_stepping(&boards[1], &bfs, (Some(25), 152183, 167022, 25));
+        check_stepping(&boards[1], &random_dfs, (None, 2268, 2021, 12));
+        check_stepping(&boards[1], &dfs, (Some(39), 326388, 155184, 40));
+        check_stepping(&boards[1], &a_star, (Some(25), 1755, 2722, 25));
+        check_stepping(&boards[1], &greedy, (Some(31), 60, 105, 31));
+
+        check_stepping(&boards[2], &bfs, (Some(23), 103512, 127898, 23));
+        check_stepping(&boards[2], &random_dfs, (None, 2284, 2021, 12));
+        check_stepping(&boards[2], &dfs, (Some(37), 45633, 34591, 40));
+        check_stepping(&boards[2], &a_star, (Some(23), 362, 582, 23));
+        check_stepping(&boards[2], &greedy, (Some(53), 539, 766, 75));
+
+        check_stepping(&boards[3], &a_star, (Some(28), 5001, 9964, 28));
+        check_stepping(&boards[3], &greedy, (Some(30), 257, 515, 56));
+    }
+
+    #[test]
+    fn runs_stop_after_exactly_the_given_number_of_states() {
+        let boards = boards();
+        let a_star = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+
+        let mut run = SearchRun::new(&boards[3], &a_star, &SolveOptions::default());
+        for processed in [1, 2, 17, 1000, 4999] {
+            assert!(run.run_until(processed));
+            assert_eq!(run.processed_states(), processed);
+            assert!(run.result().is_none());
+        }
+
+        // A run past the end stops where the search does.
+        assert!(!run.run_until(100_000));
+        assert_eq!(run.processed_states(), 5001);
+        assert!(!run.run_until(100_000));
+        assert_eq!(totals(run.result().unwrap()), (Some(28), 5001, 9964, 28));
+    }
+
+    #[test]
+    fn paused_runs_resume_where_they_stopped() {
+        let boards = boards();
+        let bfs = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+
+        let mut run = SearchRun::new(&boards[0], &bfs, &SolveOptions::default());
+        assert!(run.run_until(50_000));
+        assert!(run.run_until(50_000));
+        assert_eq!(run.processed_states(), 50_000);
+        let result = run.finish();
+        assert_eq!(totals(&result), (Some(22), 103863, 124382, 22));
+    }
+
+    #[test]
+    fn runs_on_unsupported_boards_end_before_starting() {
+        let puzzle = Puzzle::scrambled(4, 4, 20, &mut Rng::new(13)).unwrap();
+        let a_star = AStar {
+            heuristic: Arc::new(PatternDatabase::build(3, 2, &[3, 2]).unwrap()),
+            weight: Weight::Factor(1.0),
+        };
+
+        let mut run = SearchRun::new(&puzzle, &a_star, &SolveOptions::default());
+        assert!(!run.step());
+        assert_eq!(run.processed_states(), 0);
+        assert_eq!(run.result().unwrap().outcome, Outcome::UnsupportedBoard);
+    }
+
+    #[test]
+    fn pauses_dont_count_towards_the_time_limit() {
+        let puzzle = Puzzle::scrambled(4, 4, 40, &mut Rng::new(12)).unwrap();
+        let options = SolveOptions {
+            time_limit: Some(Duration::from_millis(50)),
+            ..SolveOptions::default()
+        };
+        let bfs = Bfs {
+            order: Order::Fixed(INFORMED_ORDER),
+        };
+
+        let mut run = SearchRun::new(&puzzle, &bfs, &options);
+        assert!(run.run_until(100));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(
+            run.step(),
+            "{:?}",
+            run.result().map(|result| result.outcome)
+        );
+        assert!(run.active_time() < Duration::from_millis(50));
+    }
+}
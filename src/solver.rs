@@ -0,0 +1,181 @@
+use crate::heuristic::Heuristic;
+use crate::observer::{NoObserver, SearchObserver};
+use crate::puzzle::{Direction, Order, Puzzle, SolveOptions, SolveResult, Weight};
+use crate::search_run::Stepwise;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Search algorithm which solves puzzles. New algorithms implement it to be used like the built-in ones.
+pub trait Solver: Debug + Send + Sync {
+    /// Searches for the solution of the puzzle.
+    /// `Puzzle::solve` checks `supports` and runs the parity check first,
+    /// so solvers run through it only get solvable puzzles on boards they support.
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult;
+
+    /// Searches like `solve` does, reporting events of the search to the observer as they happen.
+    /// Solvers which don't report events keep this default, which runs unobserved.
+    fn solve_observed(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        _observer: &mut dyn SearchObserver,
+    ) -> SolveResult {
+        self.solve(puzzle, options)
+    }
+
+    /// Returns whether the solver can solve boards of the given dimensions.
+    fn supports(&self, _width: usize, _height: usize) -> bool {
+        true
+    }
+
+    /// Returns whether the solver always finds the shortest solution.
+    fn finds_shortest_path(&self) -> bool {
+        false
+    }
+
+    /// Returns the heuristic guiding the solver, if it's an informed one.
+    fn heuristic(&self) -> Option<&dyn Heuristic> {
+        None
+    }
+}
+
+/// Breadth-first search.
+#[derive(Debug, Clone)]
+pub struct Bfs {
+    pub order: Order,
+}
+
+/// Depth-first search, which doesn't go deeper than the depth limit.
+#[derive(Debug, Clone)]
+pub struct Dfs {
+    pub order: Order,
+    pub depth_limit: usize,
+}
+
+/// A* search, or weighted and greedy best-first search depending on the weight.
+#[derive(Debug, Clone)]
+pub struct AStar {
+    pub heuristic: Arc<dyn Heuristic>,
+    pub weight: Weight,
+}
+
+/// Iterative-deepening A*, a depth-first search bounded by the path length plus the estimate.
+#[derive(Debug, Clone)]
+pub struct IdaStar {
+    pub heuristic: Arc<dyn Heuristic>,
+}
+
+/// DFS with depth limits of 0, 1, 2 and so on, until one of them reaches the goal.
+#[derive(Debug, Clone)]
+pub struct Iddfs {
+    pub order: Order,
+}
+
+/// BFS from both the initial state and the goal, until they meet.
+#[derive(Debug, Clone)]
+pub struct BidirectionalBfs {
+    pub order: [Direction; 4],
+}
+
+impl Solver for Bfs {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        self.start(puzzle, options, NoObserver).finish()
+    }
+
+    fn solve_observed(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: &mut dyn SearchObserver,
+    ) -> SolveResult {
+        self.start(puzzle, options, observer).finish()
+    }
+
+    fn finds_shortest_path(&self) -> bool {
+        true
+    }
+}
+
+impl Solver for Dfs {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        self.start(puzzle, options, NoObserver).finish()
+    }
+
+    fn solve_observed(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: &mut dyn SearchObserver,
+    ) -> SolveResult {
+        self.start(puzzle, options, observer).finish()
+    }
+}
+
+impl Solver for AStar {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        self.start(puzzle, options, NoObserver).finish()
+    }
+
+    fn solve_observed(
+        &self,
+        puzzle: &Puzzle,
+        options: &SolveOptions,
+        observer: &mut dyn SearchObserver,
+    ) -> SolveResult {
+        self.start(puzzle, options, observer).finish()
+    }
+
+    /// Only boards the heuristic supports.
+    fn supports(&self, width: usize, height: usize) -> bool {
+        self.heuristic.supports(width, height)
+    }
+
+    /// Weights above 1 can overestimate, even if the heuristic itself doesn't.
+    fn finds_shortest_path(&self) -> bool {
+        self.heuristic.is_admissible()
+            && matches!(self.weight, Weight::Factor(weight) if weight <= 1.0)
+    }
+
+    fn heuristic(&self) -> Option<&dyn Heuristic> {
+        Some(self.heuristic.as_ref())
+    }
+}
+
+impl Solver for IdaStar {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        puzzle.solve_ida(self.heuristic.as_ref(), options)
+    }
+
+    /// Only boards the heuristic supports.
+    fn supports(&self, width: usize, height: usize) -> bool {
+        self.heuristic.supports(width, height)
+    }
+
+    fn finds_shortest_path(&self) -> bool {
+        self.heuristic.is_admissible()
+    }
+
+    fn heuristic(&self) -> Option<&dyn Heuristic> {
+        Some(self.heuristic.as_ref())
+    }
+}
+
+impl Solver for Iddfs {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        puzzle.solve_iddfs(&self.order, options)
+    }
+
+    fn finds_shortest_path(&self) -> bool {
+        true
+    }
+}
+
+impl Solver for BidirectionalBfs {
+    fn solve(&self, puzzle: &Puzzle, options: &SolveOptions) -> SolveResult {
+        puzzle.solve_bidirectional(&self.order, options)
+    }
+
+    fn finds_shortest_path(&self) -> bool {
+        true
+    }
+}
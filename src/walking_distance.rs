@@ -0,0 +1,226 @@
+use crate::grid::Tile;
+use crate::heuristic::Heuristic;
+use crate::puzzle::Puzzle;
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+/// Most rows or columns a supported board can have. Tables grow quickly with them: those of a 4x4 board
+/// hold about 25 thousand occupancies, while building the ones of a 5x5 board runs out of memory.
+const MAX_LINES: usize = 4;
+
+thread_local! {
+    /// Tables are built on first use for every board size and reused afterwards.
+    static TABLES: RefCell<HashMap<(usize, usize), Rc<Tables>>> = RefCell::new(HashMap::new());
+}
+
+/// Number of vertical moves needed to bring every tile to its goal row plus the number of horizontal moves
+/// needed to bring every tile to its goal column, where tiles only have to swap with the empty cell,
+/// but not with each other.
+#[derive(Debug, Clone, Copy)]
+pub struct WalkingDistance;
+
+impl Heuristic for WalkingDistance {
+    fn estimate(&self, puzzle: &Puzzle) -> u32 {
+        puzzle.walking_distance_metric()
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    /// Returns whether tables of a board with given dimensions can be built.
+    fn supports(&self, width: usize, height: usize) -> bool {
+        width <= MAX_LINES && height <= MAX_LINES
+    }
+}
+
+/// Walking distance tables for a board.
+/// Vertical table looks only at which rows the tiles are in (and in which rows they should be),
+/// horizontal table does the same for columns. Every move changes just one of these,
+/// so the sum of both distances never overestimates.
+pub(crate) struct Tables {
+    width: usize,
+    height: usize,
+    vertical: LineTable,
+    horizontal: LineTable,
+}
+
+/// Distances to the goal from every reachable occupancy of lines (rows or columns).
+/// Occupancy holds, for every line, how many tiles from each goal line are in it, and which line is the empty cell in.
+struct LineTable {
+    /// Bits needed to store a single count.
+    bits: usize,
+    distances: HashMap<u128, u8>,
+}
+
+impl Tables {
+    /// Returns tables for a board with the given dimensions, building them if they weren't needed yet.
+    pub(crate) fn for_board(width: usize, height: usize) -> Rc<Tables> {
+        TABLES.with(|tables| {
+            tables
+                .borrow_mut()
+                .entry((width, height))
+                .or_insert_with(|| {
+                    Rc::new(Tables {
+                        width,
+                        height,
+                        vertical: LineTable::build(height, width),
+                        horizontal: LineTable::build(width, height),
+                    })
+                })
+                .clone()
+        })
+    }
+
+    /// Returns the walking distance of a board, given its cells in row-major order.
+    pub(crate) fn estimate(&self, cells: impl Iterator<Item = Tile>) -> u32 {
+        let height = self.height;
+
+        let mut rows = vec![0; height * height];
+        let mut columns = vec![0; self.width * self.width];
+        let mut empty = (0, 0);
+
+        for (i, value) in cells.enumerate() {
+            let (y, x) = (i / self.width, i % self.width);
+            if value == 0 {
+                empty = (y, x);
+                continue;
+            }
+            let correct_y = (value as usize - 1) / self.width;
+            let correct_x = (value as usize - 1) % self.width;
+            rows[y * height + correct_y] += 1;
+            columns[x * self.width + correct_x] += 1;
+        }
+
+        self.vertical.distance(&rows, empty.0) + self.horizontal.distance(&columns, empty.1)
+    }
+}
+
+impl LineTable {
+    /// Runs BFS from the goal, where every line holds only its own tiles and the empty cell is in the last one.
+    /// A move takes any tile from a line next to the empty cell's one and brings the empty cell there.
+    fn build(lines: usize, line_len: usize) -> LineTable {
+        let mut table = LineTable {
+            bits: bits_for(line_len),
+            distances: HashMap::new(),
+        };
+
+        let mut goal = vec![0; lines * lines];
+        for line in 0..lines {
+            goal[line * lines + line] = line_len;
+        }
+        goal[lines * lines - 1] -= 1;
+
+        let goal_key = table.key(&goal, lines - 1);
+        table.distances.insert(goal_key, 0);
+        let mut queue = VecDeque::from([(goal, lines - 1)]);
+
+        while let Some((counts, empty)) = queue.pop_front() {
+            let distance = table.distances[&table.key(&counts, empty)];
+
+            let mut next_lines = Vec::new();
+            if empty > 0 {
+                next_lines.push(empty - 1);
+            }
+            if empty < lines - 1 {
+                next_lines.push(empty + 1);
+            }
+
+            for next in next_lines {
+                for goal_line in 0..lines {
+                    if counts[next * lines + goal_line] == 0 {
+                        continue;
+                    }
+
+                    let mut next_counts = counts.clone();
+                    next_counts[next * lines + goal_line] -= 1;
+                    next_counts[empty * lines + goal_line] += 1;
+
+                    let key = table.key(&next_counts, next);
+                    if let Entry::Vacant(entry) = table.distances.entry(key) {
+                        entry.insert(distance + 1);
+                        queue.push_back((next_counts, next));
+                    }
+                }
+            }
+        }
+
+        table
+    }
+
+    fn key(&self, counts: &[usize], empty: usize) -> u128 {
+        let mut key = empty as u128;
+        for count in counts {
+            key = (key << self.bits) | *count as u128;
+        }
+        key
+    }
+
+    fn distance(&self, counts: &[usize], empty: usize) -> u32 {
+        self.distances[&self.key(counts, empty)] as u32
+    }
+}
+
+/// Returns the number of bits needed to store values from 0 to max.
+fn bits_for(max: usize) -> usize {
+    (usize::BITS - max.leading_zeros()) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::{check_updates, Manhattan};
+    use crate::puzzle::{SolveOptions, Weight};
+    use crate::rng::Rng;
+    use crate::solver::AStar;
+    use std::sync::Arc;
+
+    #[test]
+    fn walking_distance_updates_match_estimates() {
+        check_updates(&WalkingDistance, 3, 3, 1);
+        check_updates(&WalkingDistance, 4, 4, 2);
+        check_updates(&WalkingDistance, 2, 4, 3);
+    }
+
+    #[test]
+    fn fifteen_puzzle_table_has_the_known_size() {
+        // Walking distance tables of the 4x4 board are known to hold 24964 occupancies, at most 35 moves away.
+        let table = LineTable::build(4, 4);
+        assert_eq!(table.distances.len(), 24964);
+        assert_eq!(table.distances.values().max(), Some(&35));
+    }
+
+    #[test]
+    fn walking_distance_lies_between_manhattan_and_the_solution_length() {
+        assert_eq!(Puzzle::goal(4, 4).walking_distance_metric(), 0);
+
+        let mut rng = Rng::new(6);
+        let solver = AStar {
+            heuristic: Arc::new(Manhattan),
+            weight: Weight::Factor(1.0),
+        };
+        for (width, height) in [(3, 3), (2, 4), (4, 2), (3, 2)] {
+            for _ in 0..5 {
+                let puzzle = Puzzle::random_solvable(width, height, &mut rng).unwrap();
+                let optimal = puzzle
+                    .solve(&solver, &SolveOptions::default())
+                    .path
+                    .unwrap();
+                let metric = puzzle.walking_distance_metric();
+                assert!(puzzle.manhattan_metric() <= metric, "{}", puzzle);
+                assert!(metric as usize <= optimal.len(), "{}", puzzle);
+            }
+        }
+    }
+
+    #[test]
+    fn boards_are_supported_while_their_tables_can_be_built() {
+        assert!(WalkingDistance.supports(4, 4));
+        assert!(WalkingDistance.supports(2, 4));
+        assert!(!WalkingDistance.supports(5, 5));
+        assert!(!WalkingDistance.supports(5, 4));
+        assert!(!WalkingDistance.supports(8, 2));
+    }
+}